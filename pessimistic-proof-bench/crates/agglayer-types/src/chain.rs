@@ -0,0 +1,323 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use pessimistic_proof::local_exit_tree::{hasher::Keccak256Hasher, LocalExitTree};
+use serde::{Deserialize, Serialize};
+
+use crate::{Certificate, CertificateId, Digest, Height, NetworkId};
+
+#[derive(Debug, thiserror::Error, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChainError {
+    /// The certificate is emitted by another network than the one of the
+    /// chain.
+    #[error("Certificate of another network. expected: {expected}, got: {got}")]
+    WrongNetwork { expected: NetworkId, got: NetworkId },
+    /// The certificate skips at least one height.
+    #[error("Height gap. expected: {expected}, got: {got}")]
+    HeightGap { expected: Height, got: Height },
+    /// The certificate is already accepted at its height.
+    #[error("Certificate already accepted at height {height}: {certificate_id}")]
+    DuplicateCertificate {
+        height: Height,
+        certificate_id: CertificateId,
+    },
+    /// Another certificate is already accepted at the same height.
+    #[error("Equivocation at height {height}. accepted: {accepted}, conflicting: {conflicting}")]
+    Equivocation {
+        height: Height,
+        accepted: CertificateId,
+        conflicting: CertificateId,
+    },
+    /// The certificate does not start from the local exit root reached by the
+    /// previous height.
+    #[error(
+        "Mismatch on the previous local exit root at height {height}. expected: {expected}, \
+         got: {got}"
+    )]
+    MismatchPrevLocalExitRoot {
+        height: Height,
+        expected: Digest,
+        got: Digest,
+    },
+    /// The network already has a chain.
+    #[error("Network {0} is already tracked")]
+    AlreadyTracked(NetworkId),
+}
+
+/// Certificate accepted at one height of a [`CertificateChain`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChainEntry {
+    pub certificate_id: CertificateId,
+    pub prev_local_exit_root: Digest,
+    pub new_local_exit_root: Digest,
+}
+
+/// Sequence of the certificates accepted for one network, indexed by height.
+///
+/// Each certificate has to start from the local exit root reached by the
+/// previous height, and only one certificate can be accepted per height.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CertificateChain {
+    network_id: NetworkId,
+    genesis_local_exit_root: Digest,
+    entries: Vec<ChainEntry>,
+}
+
+impl CertificateChain {
+    /// Creates an empty chain starting from the given local exit root.
+    pub fn new(network_id: NetworkId, genesis_local_exit_root: Digest) -> Self {
+        Self {
+            network_id,
+            genesis_local_exit_root,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the network of this chain.
+    pub fn network_id(&self) -> NetworkId {
+        self.network_id
+    }
+
+    /// Returns the height expected for the next certificate.
+    pub fn next_height(&self) -> Height {
+        self.entries.len() as Height
+    }
+
+    /// Returns the local exit root reached by the last accepted certificate.
+    pub fn latest_local_exit_root(&self) -> Digest {
+        self.entries
+            .last()
+            .map(|entry| entry.new_local_exit_root)
+            .unwrap_or(self.genesis_local_exit_root)
+    }
+
+    /// Returns the certificate accepted at the given height.
+    pub fn get(&self, height: Height) -> Option<&ChainEntry> {
+        self.entries.get(usize::try_from(height).ok()?)
+    }
+
+    /// Checks that the given [`Certificate`] extends this chain, without
+    /// recording it.
+    pub fn check(&self, certificate: &Certificate) -> Result<CertificateId, ChainError> {
+        if certificate.network_id != self.network_id {
+            return Err(ChainError::WrongNetwork {
+                expected: self.network_id,
+                got: certificate.network_id,
+            });
+        }
+
+        let certificate_id = certificate.hash();
+        let height = certificate.height;
+        if let Some(accepted) = self.get(height) {
+            if accepted.certificate_id == certificate_id {
+                return Err(ChainError::DuplicateCertificate {
+                    height,
+                    certificate_id,
+                });
+            }
+
+            return Err(ChainError::Equivocation {
+                height,
+                accepted: accepted.certificate_id,
+                conflicting: certificate_id,
+            });
+        }
+
+        if height != self.next_height() {
+            return Err(ChainError::HeightGap {
+                expected: self.next_height(),
+                got: height,
+            });
+        }
+
+        let expected = self.latest_local_exit_root();
+        if certificate.prev_local_exit_root != expected {
+            return Err(ChainError::MismatchPrevLocalExitRoot {
+                height,
+                expected,
+                got: certificate.prev_local_exit_root,
+            });
+        }
+
+        Ok(certificate_id)
+    }
+
+    /// Appends the given [`Certificate`] to this chain. Returns its
+    /// [`CertificateId`].
+    pub fn accept(&mut self, certificate: &Certificate) -> Result<CertificateId, ChainError> {
+        let certificate_id = self.check(certificate)?;
+        self.entries.push(ChainEntry {
+            certificate_id,
+            prev_local_exit_root: certificate.prev_local_exit_root,
+            new_local_exit_root: certificate.new_local_exit_root,
+        });
+
+        Ok(certificate_id)
+    }
+}
+
+/// The [`CertificateChain`] of every network.
+///
+/// The chain of a network starts from the empty local exit tree, unless the
+/// network is tracked beforehand from another local exit root.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChainTracker {
+    chains: BTreeMap<NetworkId, CertificateChain>,
+}
+
+impl ChainTracker {
+    /// Creates a new tracker without any certificate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts the chain of the given network from the given local exit root,
+    /// e.g. for a network migrated with a non-empty local exit tree.
+    pub fn track(
+        &mut self,
+        network_id: NetworkId,
+        genesis_local_exit_root: Digest,
+    ) -> Result<(), ChainError> {
+        match self.chains.entry(network_id) {
+            Entry::Occupied(_) => Err(ChainError::AlreadyTracked(network_id)),
+            Entry::Vacant(entry) => {
+                entry.insert(CertificateChain::new(network_id, genesis_local_exit_root));
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the chain of the given network, if it is tracked or has any
+    /// certificate.
+    pub fn chain(&self, network_id: NetworkId) -> Option<&CertificateChain> {
+        self.chains.get(&network_id)
+    }
+
+    /// Appends the given [`Certificate`] to the chain of its network. Returns
+    /// its [`CertificateId`].
+    ///
+    /// The chain of a network which is not tracked yet is only created once
+    /// its first certificate is accepted.
+    pub fn accept(&mut self, certificate: &Certificate) -> Result<CertificateId, ChainError> {
+        if let Some(chain) = self.chains.get_mut(&certificate.network_id) {
+            return chain.accept(certificate);
+        }
+
+        let mut chain = CertificateChain::new(
+            certificate.network_id,
+            LocalExitTree::<Keccak256Hasher>::default().get_root(),
+        );
+        let certificate_id = chain.accept(certificate)?;
+        self.chains.insert(certificate.network_id, chain);
+
+        Ok(certificate_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::random;
+
+    use super::*;
+
+    fn next(certificate: &Certificate) -> Certificate {
+        Certificate::new_for_test(certificate.network_id, certificate.height + 1)
+            .with_new_local_exit_root(Digest(random()))
+    }
+
+    #[test]
+    fn chain_sequencing() {
+        let network_id = random::<u32>().into();
+        let mut tracker = ChainTracker::new();
+
+        // A rejected first certificate does not create the chain
+        let gap = Certificate::new_for_test(network_id, 1);
+        assert!(tracker.accept(&gap).is_err());
+        assert!(tracker.chain(network_id).is_none());
+
+        let first =
+            Certificate::new_for_test(network_id, 0).with_new_local_exit_root(Digest(random()));
+        let first_id = tracker.accept(&first).unwrap();
+        assert_eq!(
+            tracker.accept(&first),
+            Err(ChainError::DuplicateCertificate {
+                height: 0,
+                certificate_id: first_id,
+            })
+        );
+
+        let conflicting = first.clone().with_new_local_exit_root(Digest(random()));
+        assert_eq!(
+            tracker.accept(&conflicting),
+            Err(ChainError::Equivocation {
+                height: 0,
+                accepted: first_id,
+                conflicting: conflicting.hash(),
+            })
+        );
+
+        // The next height has to start from the local exit root of the first
+        let mut second = next(&first);
+        assert_eq!(
+            tracker.accept(&second),
+            Err(ChainError::MismatchPrevLocalExitRoot {
+                height: 1,
+                expected: first.new_local_exit_root,
+                got: second.prev_local_exit_root,
+            })
+        );
+        second.prev_local_exit_root = first.new_local_exit_root;
+
+        let third = next(&second);
+        assert_eq!(
+            tracker.accept(&third),
+            Err(ChainError::HeightGap {
+                expected: 1,
+                got: 2,
+            })
+        );
+
+        let second_id = tracker.accept(&second).unwrap();
+        let chain = tracker.chain(network_id).unwrap();
+        assert_eq!(chain.next_height(), 2);
+        assert_eq!(chain.get(1).unwrap().certificate_id, second_id);
+        assert_eq!(chain.latest_local_exit_root(), second.new_local_exit_root);
+
+        let mut other_network =
+            CertificateChain::new(network_id.wrapping_add(1).into(), Digest(random()));
+        assert_eq!(
+            other_network.accept(&first),
+            Err(ChainError::WrongNetwork {
+                expected: other_network.network_id(),
+                got: network_id,
+            })
+        );
+    }
+
+    #[test]
+    fn chain_from_migrated_network() {
+        let network_id = random::<u32>().into();
+        let genesis_local_exit_root = Digest(random());
+        let mut tracker = ChainTracker::new();
+        tracker.track(network_id, genesis_local_exit_root).unwrap();
+        assert_eq!(
+            tracker.track(network_id, genesis_local_exit_root),
+            Err(ChainError::AlreadyTracked(network_id))
+        );
+
+        let mut first =
+            Certificate::new_for_test(network_id, 0).with_new_local_exit_root(Digest(random()));
+        assert_eq!(
+            tracker.accept(&first),
+            Err(ChainError::MismatchPrevLocalExitRoot {
+                height: 0,
+                expected: genesis_local_exit_root,
+                got: first.prev_local_exit_root,
+            })
+        );
+
+        first.prev_local_exit_root = genesis_local_exit_root;
+        tracker.accept(&first).unwrap();
+        let chain = tracker.chain(network_id).unwrap();
+        assert_eq!(chain.latest_local_exit_root(), first.new_local_exit_root);
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CertificateHeader, CertificateId, CertificateIndex, CertificateStatus, Digest,
+    EpochConfiguration, EpochNumber, Height, NetworkId,
+};
+
+#[derive(Debug, thiserror::Error, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EpochError {
+    /// The epoch duration has to be non-zero.
+    #[error("Invalid epoch duration: 0")]
+    InvalidEpochDuration,
+    /// The block is before the genesis block of the agglayer.
+    #[error("Block {block} before the genesis block {genesis_block}")]
+    BlockBeforeGenesis { block: u64, genesis_block: u64 },
+    /// The block belongs to another epoch than the open one.
+    #[error("Block of epoch {block_epoch} while epoch {open_epoch} is open")]
+    EpochMismatch {
+        open_epoch: EpochNumber,
+        block_epoch: EpochNumber,
+    },
+    /// Only proven certificates can be packed.
+    #[error("Certificate {0} is not proven")]
+    NotProven(CertificateId),
+    /// The certificate is already assigned to an epoch.
+    #[error("Certificate {0} is already packed")]
+    AlreadyPacked(CertificateId),
+    /// The network already has the maximum number of certificates in the
+    /// open epoch.
+    #[error("Network {network_id} reached its certificate limit in epoch {epoch_number}")]
+    NetworkLimitReached {
+        network_id: NetworkId,
+        epoch_number: EpochNumber,
+    },
+    /// The epoch ends beyond the last L1 block.
+    #[error("Epoch {0} out of range")]
+    EpochOutOfRange(EpochNumber),
+}
+
+impl EpochConfiguration {
+    /// Returns the epoch of the given L1 block.
+    pub fn epoch_of(&self, block: u64) -> Result<EpochNumber, EpochError> {
+        if self.epoch_duration == 0 {
+            return Err(EpochError::InvalidEpochDuration);
+        }
+
+        let Some(elapsed) = block.checked_sub(self.genesis_block) else {
+            return Err(EpochError::BlockBeforeGenesis {
+                block,
+                genesis_block: self.genesis_block,
+            });
+        };
+
+        Ok(elapsed / self.epoch_duration)
+    }
+
+    /// Returns the first and last L1 blocks of the given epoch.
+    pub fn block_range(&self, epoch_number: EpochNumber) -> Result<(u64, u64), EpochError> {
+        if self.epoch_duration == 0 {
+            return Err(EpochError::InvalidEpochDuration);
+        }
+
+        epoch_number
+            .checked_mul(self.epoch_duration)
+            .and_then(|elapsed| elapsed.checked_add(self.genesis_block))
+            .and_then(|start| Some((start, start.checked_add(self.epoch_duration - 1)?)))
+            .ok_or(EpochError::EpochOutOfRange(epoch_number))
+    }
+}
+
+/// Certificate included in a [`SettlementBatch`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PackedCertificate {
+    pub certificate_index: CertificateIndex,
+    pub certificate_id: CertificateId,
+    pub network_id: NetworkId,
+    pub height: Height,
+    pub new_local_exit_root: Digest,
+}
+
+/// Certificates to settle on L1 for one closed epoch, ordered by certificate
+/// index.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SettlementBatch {
+    pub epoch_number: EpochNumber,
+    pub start_block: u64,
+    pub end_block: u64,
+    pub certificates: Vec<PackedCertificate>,
+}
+
+/// Assigns the proven certificates to the open epoch, in order of arrival.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EpochPacker {
+    config: EpochConfiguration,
+    /// Maximum number of certificates per network and per epoch. None if
+    /// unlimited.
+    max_certificates_per_network: Option<usize>,
+    open_epoch: EpochNumber,
+    certificates: Vec<PackedCertificate>,
+    per_network: BTreeMap<NetworkId, usize>,
+}
+
+impl EpochPacker {
+    /// Creates a packer with the epoch of the given L1 block open, allowing
+    /// one certificate per network and per epoch.
+    pub fn new(config: EpochConfiguration, block: u64) -> Result<Self, EpochError> {
+        let open_epoch = config.epoch_of(block)?;
+
+        Ok(Self {
+            config,
+            max_certificates_per_network: Some(1),
+            open_epoch,
+            certificates: Vec::new(),
+            per_network: BTreeMap::new(),
+        })
+    }
+
+    /// Sets the maximum number of certificates per network and per epoch.
+    pub fn with_max_certificates_per_network(mut self, max: Option<usize>) -> Self {
+        self.max_certificates_per_network = max;
+        self
+    }
+
+    /// Returns the open epoch.
+    pub fn open_epoch(&self) -> EpochNumber {
+        self.open_epoch
+    }
+
+    /// Assigns the given proven certificate, received at the given L1 block,
+    /// to the open epoch, and fills its epoch number and certificate index.
+    ///
+    /// The status of the certificate is left to the caller, which makes it a
+    /// candidate for settlement through [`CertificateLifecycle::transition`].
+    ///
+    /// [`CertificateLifecycle::transition`]: crate::CertificateLifecycle::transition
+    pub fn pack(
+        &mut self,
+        header: &mut CertificateHeader,
+        block: u64,
+    ) -> Result<(EpochNumber, CertificateIndex), EpochError> {
+        let block_epoch = self.config.epoch_of(block)?;
+        if block_epoch != self.open_epoch {
+            return Err(EpochError::EpochMismatch {
+                open_epoch: self.open_epoch,
+                block_epoch,
+            });
+        }
+
+        if header.epoch_number.is_some() || header.certificate_index.is_some() {
+            return Err(EpochError::AlreadyPacked(header.certificate_id));
+        }
+
+        if !header
+            .status
+            .can_transition_to(&CertificateStatus::Candidate)
+        {
+            return Err(EpochError::NotProven(header.certificate_id));
+        }
+
+        let count = self.per_network.entry(header.network_id).or_default();
+        if self
+            .max_certificates_per_network
+            .is_some_and(|max| *count >= max)
+        {
+            return Err(EpochError::NetworkLimitReached {
+                network_id: header.network_id,
+                epoch_number: self.open_epoch,
+            });
+        }
+        *count += 1;
+
+        let certificate_index = self.certificates.len() as CertificateIndex;
+        self.certificates.push(PackedCertificate {
+            certificate_index,
+            certificate_id: header.certificate_id,
+            network_id: header.network_id,
+            height: header.height,
+            new_local_exit_root: header.new_local_exit_root,
+        });
+
+        header.epoch_number = Some(self.open_epoch);
+        header.certificate_index = Some(certificate_index);
+
+        Ok((self.open_epoch, certificate_index))
+    }
+
+    /// Closes the open epoch and opens the next one. Returns the settlement
+    /// batch of the closed epoch.
+    pub fn close_epoch(&mut self) -> Result<SettlementBatch, EpochError> {
+        let epoch_number = self.open_epoch;
+        let next_epoch = epoch_number
+            .checked_add(1)
+            .ok_or(EpochError::EpochOutOfRange(epoch_number))?;
+        self.open(next_epoch)
+    }
+
+    /// Opens the epoch of the given L1 block. Returns the settlement batch of
+    /// the epoch closed on the way, if it has any certificate. The elapsed
+    /// epochs in between are empty and skipped.
+    pub fn advance_to(&mut self, block: u64) -> Result<Option<SettlementBatch>, EpochError> {
+        let block_epoch = self.config.epoch_of(block)?;
+        if block_epoch <= self.open_epoch {
+            return Ok(None);
+        }
+
+        let batch = self.open(block_epoch)?;
+        Ok((!batch.certificates.is_empty()).then_some(batch))
+    }
+
+    /// Closes the open epoch in favor of the given one.
+    fn open(&mut self, epoch_number: EpochNumber) -> Result<SettlementBatch, EpochError> {
+        let closed_epoch = self.open_epoch;
+        let (start_block, end_block) = self.config.block_range(closed_epoch)?;
+
+        self.open_epoch = epoch_number;
+        self.per_network.clear();
+
+        Ok(SettlementBatch {
+            epoch_number: closed_epoch,
+            start_block,
+            end_block,
+            certificates: std::mem::take(&mut self.certificates),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::random;
+
+    use super::*;
+    use crate::CertificateLifecycle;
+
+    fn proven(network_id: u32) -> CertificateHeader {
+        CertificateHeader {
+            network_id: network_id.into(),
+            height: random::<u32>() as Height,
+            epoch_number: None,
+            certificate_index: None,
+            certificate_id: Digest(random()),
+            prev_local_exit_root: Digest(random()),
+            new_local_exit_root: Digest(random()),
+            metadata: Digest::default(),
+            status: CertificateStatus::Proven,
+            settlement_tx_hash: None,
+        }
+    }
+
+    #[test]
+    fn epoch_of_block() {
+        let config = EpochConfiguration {
+            genesis_block: 100,
+            epoch_duration: 10,
+        };
+        assert_eq!(config.epoch_of(100), Ok(0));
+        assert_eq!(config.epoch_of(119), Ok(1));
+        assert_eq!(config.block_range(1), Ok((110, 119)));
+        assert_eq!(
+            config.block_range(u64::MAX / 10),
+            Err(EpochError::EpochOutOfRange(u64::MAX / 10))
+        );
+        assert_eq!(
+            config.epoch_of(99),
+            Err(EpochError::BlockBeforeGenesis {
+                block: 99,
+                genesis_block: 100,
+            })
+        );
+    }
+
+    #[test]
+    fn epoch_packing() {
+        let config = EpochConfiguration {
+            genesis_block: 0,
+            epoch_duration: 10,
+        };
+        let mut packer = EpochPacker::new(config, 0).unwrap();
+
+        let mut first = proven(1);
+        let mut lifecycle = CertificateLifecycle::new();
+        lifecycle.register(first.certificate_id).unwrap();
+        lifecycle
+            .transition(first.certificate_id, CertificateStatus::Proven)
+            .unwrap();
+
+        assert_eq!(packer.pack(&mut first, 3), Ok((0, 0)));
+        assert_eq!(first.epoch_number, Some(0));
+        assert_eq!(first.certificate_index, Some(0));
+
+        // The caller makes it a candidate through the lifecycle
+        assert_eq!(first.status, CertificateStatus::Proven);
+        lifecycle
+            .transition(first.certificate_id, CertificateStatus::Candidate)
+            .unwrap();
+        first.status = lifecycle.status(&first.certificate_id).unwrap().clone();
+        assert_eq!(first.status, CertificateStatus::Candidate);
+        assert_eq!(
+            packer.pack(&mut first, 4),
+            Err(EpochError::AlreadyPacked(first.certificate_id))
+        );
+
+        for status in [CertificateStatus::Pending, CertificateStatus::Settled] {
+            let mut unproven = proven(2);
+            unproven.status = status;
+            assert_eq!(
+                packer.pack(&mut unproven, 4),
+                Err(EpochError::NotProven(unproven.certificate_id))
+            );
+        }
+
+        // One certificate per network and per epoch by default
+        assert_eq!(
+            packer.pack(&mut proven(1), 5),
+            Err(EpochError::NetworkLimitReached {
+                network_id: 1.into(),
+                epoch_number: 0,
+            })
+        );
+        let mut second = proven(2);
+        assert_eq!(packer.pack(&mut second, 5), Ok((0, 1)));
+
+        assert_eq!(
+            packer.pack(&mut proven(3), 25),
+            Err(EpochError::EpochMismatch {
+                open_epoch: 0,
+                block_epoch: 2,
+            })
+        );
+
+        // The empty epoch 1 is skipped
+        let batch = packer.advance_to(25).unwrap().unwrap();
+        assert_eq!(packer.open_epoch(), 2);
+        assert_eq!(batch.epoch_number, 0);
+        assert_eq!((batch.start_block, batch.end_block), (0, 9));
+        assert_eq!(
+            batch
+                .certificates
+                .iter()
+                .map(|certificate| certificate.certificate_id)
+                .collect::<Vec<_>>(),
+            vec![first.certificate_id, second.certificate_id]
+        );
+
+        // The limit is configurable
+        let mut packer = packer.with_max_certificates_per_network(None);
+        assert_eq!(packer.pack(&mut proven(1), 25), Ok((2, 0)));
+        assert_eq!(packer.pack(&mut proven(1), 26), Ok((2, 1)));
+    }
+
+    #[test]
+    fn epoch_packing_from_a_later_block() {
+        let config = EpochConfiguration {
+            genesis_block: 100,
+            epoch_duration: 10,
+        };
+        assert_eq!(
+            EpochPacker::new(config.clone(), 99).unwrap_err(),
+            EpochError::BlockBeforeGenesis {
+                block: 99,
+                genesis_block: 100,
+            }
+        );
+
+        let mut packer = EpochPacker::new(config, 1_000_005).unwrap();
+        assert_eq!(packer.open_epoch(), 99_990);
+        assert_eq!(packer.pack(&mut proven(1), 1_000_005), Ok((99_990, 0)));
+
+        // Advancing over many empty epochs allocates no batch for them
+        assert!(packer.advance_to(u64::MAX).unwrap().is_some());
+        assert_eq!(packer.advance_to(u64::MAX), Ok(None));
+        assert_eq!(packer.open_epoch(), (u64::MAX - 100) / 10);
+
+        // The last epoch ends beyond the last L1 block
+        assert_eq!(
+            packer.close_epoch(),
+            Err(EpochError::EpochOutOfRange((u64::MAX - 100) / 10))
+        );
+    }
+}
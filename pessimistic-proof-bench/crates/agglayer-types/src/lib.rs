@@ -1,5 +1,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 
+use pessimistic_proof::eip712::{domain_separator, Eip712Domain};
+use pessimistic_proof::forced_withdrawals::commit_forced_withdrawals;
 use pessimistic_proof::global_index::GlobalIndex;
 pub use pessimistic_proof::keccak::digest::Digest;
 use pessimistic_proof::keccak::keccak256_combine;
@@ -7,13 +9,22 @@ use pessimistic_proof::local_balance_tree::{LocalBalanceTree, LOCAL_BALANCE_TREE
 use pessimistic_proof::local_exit_tree::hasher::Keccak256Hasher;
 use pessimistic_proof::local_exit_tree::{LocalExitTree, LocalExitTreeError};
 use pessimistic_proof::local_state::StateCommitment;
-use pessimistic_proof::multi_batch_header::signature_commitment;
+use pessimistic_proof::multi_batch_header::CommitmentMode;
+use pessimistic_proof::native_supply::{
+    NativeSupply, NativeSupplyLedger, NativeSupplyPath, NativeSupplyTree,
+};
+use pessimistic_proof::network_config::NetworkConfig;
 use pessimistic_proof::nullifier_tree::{NullifierTree, NULLIFIER_TREE_DEPTH};
+use pessimistic_proof::outflow_limits_tree::{
+    OutflowLimitPath, OutflowLimitsTree, OUTFLOW_LIMITS_TREE_DEPTH,
+};
+use pessimistic_proof::signer_set::{apply_signer_rotation, commit_signer_set, SignerRotation};
 use pessimistic_proof::utils::smt::{Smt, SmtError};
 use pessimistic_proof::utils::{FromBool as _, Hashable as _};
+use pessimistic_proof::witness::Witness;
 use pessimistic_proof::LocalNetworkState;
 use pessimistic_proof::{
-    bridge_exit::{BridgeExit, TokenInfo},
+    bridge_exit::{BridgeExit, LeafType, TokenInfo, L1_ETH},
     imported_bridge_exit::{commit_imported_bridge_exits, ImportedBridgeExit},
     local_balance_tree::LocalBalancePath,
     multi_batch_header::MultiBatchHeader,
@@ -31,7 +42,16 @@ pub type Metadata = Digest;
 pub use agglayer_primitives as primitives;
 // Re-export common primitives again as agglayer-types root types
 pub use agglayer_primitives::{Address, Signature, SignatureError, B256, U256, U512};
+pub use chain::{CertificateChain, ChainEntry, ChainError, ChainTracker};
+pub use epoch::{EpochError, EpochPacker, PackedCertificate, SettlementBatch};
+pub use lifecycle::{CertificateLifecycle, LifecycleError, LifecycleStore, StatusChange};
 pub use pessimistic_proof::bridge_exit::NetworkId;
+pub use signer::{CertificateSigner, KeystoreSigner, LocalSigner, SignerError};
+
+mod chain;
+mod epoch;
+mod lifecycle;
+mod signer;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ExecutionMode {
@@ -94,6 +114,66 @@ pub enum Error {
         source: pessimistic_proof::utils::smt::SmtError,
         token: TokenInfo,
     },
+    /// The bridge exits of the given token exceed its outflow limit.
+    #[error("Outflow limit exceeded. token: {token:?}, limit: {limit}, outflow: {outflow}")]
+    OutflowLimitExceeded {
+        token: TokenInfo,
+        limit: U256,
+        outflow: U256,
+    },
+    /// The cumulative outflow of the given token overflows.
+    #[error("Outflow overflow. token: {0:?}")]
+    OutflowOverflow(TokenInfo),
+    /// The outflow limit proof for the given token cannot be generated.
+    #[error("Unable to generate the outflow limit proof. token: {token:?}, error: {source}")]
+    OutflowLimitProofGenerationFailed {
+        source: pessimistic_proof::utils::smt::SmtError,
+        token: TokenInfo,
+    },
+    /// The signer is not part of the authorized signers.
+    #[error("Unauthorized signer: {0}")]
+    UnauthorizedSigner(Address),
+    /// The signer rotation is not signed by an authorized signer, or replaces
+    /// it by an already authorized signer.
+    #[error("Invalid signer rotation")]
+    InvalidSignerRotation,
+    /// The signer rotation is given while no signer set is committed.
+    #[error("Unexpected signer rotation, any signer is accepted")]
+    UnexpectedSignerRotation,
+    /// The signature of the certificate cannot be recovered.
+    #[error("Invalid signature")]
+    InvalidSignature,
+    /// The certificate is signed by another signer than the expected one.
+    #[error("Invalid signer. expected: {expected}, recovered: {recovered}")]
+    InvalidSigner {
+        expected: Address,
+        recovered: Address,
+    },
+    /// The certificate is signed with another scheme or domain than the ones
+    /// configured for its network.
+    #[error("Invalid commitment mode. declared: {declared:?}, expected: {expected:?}")]
+    InvalidCommitmentMode {
+        declared: CommitmentMode,
+        expected: CommitmentMode,
+    },
+    /// The network is already frozen.
+    #[error("The network is already frozen")]
+    AlreadyFrozen,
+    /// The network is not frozen.
+    #[error("The network is not frozen")]
+    NotFrozen,
+    /// The bridge exit is not allowed while the network is frozen.
+    #[error("Bridge exit not covered by the forced withdrawals of the frozen network: {0}")]
+    BridgeExitWhileFrozen(Digest),
+    /// The cumulative native supply of the given token overflows.
+    #[error("Native supply overflow. token: {0:?}")]
+    NativeSupplyOverflow(TokenInfo),
+    /// The native supply proof for the given token cannot be generated.
+    #[error("Unable to generate the native supply proof. token: {token:?}, error: {source}")]
+    NativeSupplyProofGenerationFailed {
+        source: pessimistic_proof::utils::smt::SmtError,
+        token: TokenInfo,
+    },
     /// The nullifier path for the given imported bridge exit cannot be
     /// generated.
     #[error(
@@ -121,6 +201,47 @@ pub enum Error {
     InvalidSmtOperation(#[from] SmtError),
 }
 
+/// Inconsistencies of a [`Certificate`] detectable without the state of its
+/// network.
+#[derive(Debug, thiserror::Error, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The same global index is imported more than once.
+    #[error("Duplicate imported global index: {0:?}")]
+    DuplicateGlobalIndex(GlobalIndex),
+    /// The imported bridge exit is not destined to the network of the
+    /// certificate.
+    #[error(
+        "Imported bridge exit destined to another network. global index: {global_index:?}, \
+         dest_network: {dest_network}"
+    )]
+    InvalidImportedExitDestination {
+        global_index: GlobalIndex,
+        dest_network: NetworkId,
+    },
+    /// The bridge exit is destined to the network of the certificate.
+    #[error("Bridge exit to self: {0}")]
+    BridgeExitToSelf(Digest),
+    /// The imported bridge exit originates from the network of the
+    /// certificate.
+    #[error("Imported bridge exit from self: {0:?}")]
+    ImportedExitFromSelf(GlobalIndex),
+    /// The imported bridge exits should refer to one and the same L1 info root.
+    #[error("Imported bridge exits refer to multiple L1 info root")]
+    MultipleL1InfoRoot,
+    /// The message is not originating from the network which emitted it.
+    #[error(
+        "Invalid message origin network. exit: {exit}, declared: {declared}, expected: {expected}"
+    )]
+    InvalidMessageOrigin {
+        exit: Digest,
+        declared: u32,
+        expected: u32,
+    },
+    /// The native token of L1 is referred to with another origin network.
+    #[error("Invalid L1 ETH token info: {0:?}")]
+    InvalidL1EthTokenInfo(TokenInfo),
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, thiserror::Error, PartialEq, Eq)]
 pub enum CertificateStatusError {
     /// Failure on the pessimistic proof execution, either natively or in the
@@ -170,6 +291,10 @@ impl std::fmt::Display for GenerationType {
 pub enum ProofVerificationError {
     #[error("Version mismatch: {0}")]
     VersionMismatch(String),
+    /// The proof is verified against another verification key than the one
+    /// of the prover.
+    #[error("Verification key mismatch. expected: {expected}, got: {got}")]
+    VkeyMismatch { expected: String, got: String },
     #[error("Core machine verification error: {0}")]
     Core(String),
     #[error("Recursion verification error: {0}")]
@@ -235,6 +360,12 @@ pub struct Certificate {
     pub signature: Signature,
     /// Fixed size field of arbitrary data for the chain needs.
     pub metadata: Metadata,
+    /// Replacement of one of the authorized signers of the network.
+    #[serde(default)]
+    pub signer_rotation: Option<SignerRotation>,
+    /// Scheme of the commitment covered by the signature.
+    #[serde(default)]
+    pub commitment_mode: CommitmentMode,
 }
 
 #[cfg(any(test, feature = "testutils"))]
@@ -253,10 +384,57 @@ impl Default for Certificate {
             imported_bridge_exits: Default::default(),
             signature,
             metadata: Default::default(),
+            signer_rotation: None,
+            commitment_mode: Default::default(),
         }
     }
 }
 
+/// Signs the replacement of the signer of the given wallet by the new signer,
+/// on top of the given signer set.
+#[cfg(any(test, feature = "testutils"))]
+pub fn compute_signer_rotation(
+    network_id: NetworkId,
+    signer_set_root: Digest,
+    new_signer: Address,
+    wallet: &ethers::signers::LocalWallet,
+) -> SignerRotation {
+    use ethers::signers::Signer;
+
+    let old_signer: Address = wallet.address().0.into();
+    let commitment =
+        SignerRotation::commitment(*network_id, signer_set_root, old_signer, new_signer);
+    let signature = wallet.sign_hash(commitment.0.into()).unwrap();
+
+    SignerRotation {
+        old_signer,
+        new_signer,
+        signature: Signature::new(
+            U256::from_limbs(signature.r.0),
+            U256::from_limbs(signature.s.0),
+            signature.recovery_id().unwrap().is_y_odd(),
+        ),
+    }
+}
+
+/// Signs the commitment of the given [`Certificate`], according to its
+/// [`CommitmentMode`].
+#[cfg(any(test, feature = "testutils"))]
+pub fn compute_certificate_signature(
+    certificate: &Certificate,
+    wallet: &ethers::signers::LocalWallet,
+) -> Signature {
+    let signature = wallet
+        .sign_hash(certificate.signature_commitment().0.into())
+        .unwrap();
+
+    Signature::new(
+        U256::from_limbs(signature.r.0),
+        U256::from_limbs(signature.s.0),
+        signature.recovery_id().unwrap().is_y_odd(),
+    )
+}
+
 #[cfg(any(test, feature = "testutils"))]
 pub fn compute_signature_info(
     new_local_exit_root: Digest,
@@ -305,6 +483,8 @@ impl Certificate {
             imported_bridge_exits: Default::default(),
             signature,
             metadata: Default::default(),
+            signer_rotation: None,
+            commitment_mode: Default::default(),
         }
     }
 
@@ -320,7 +500,7 @@ impl Certificate {
         let commit_imported_bridge_exits =
             keccak256_combine(self.imported_bridge_exits.iter().map(|exit| exit.hash()));
 
-        keccak256_combine([
+        let mut id = keccak256_combine([
             self.network_id.to_be_bytes().as_slice(),
             self.height.to_be_bytes().as_slice(),
             self.prev_local_exit_root.as_slice(),
@@ -328,7 +508,44 @@ impl Certificate {
             commit_bridge_exits.as_slice(),
             commit_imported_bridge_exits.as_slice(),
             self.metadata.as_slice(),
-        ])
+        ]);
+
+        // Only committed when set, to keep the identifiers of the certificates
+        // without rotation nor EIP-712 signature unchanged
+        if let Some(rotation) = &self.signer_rotation {
+            id = keccak256_combine([
+                id.as_slice(),
+                rotation.old_signer.as_slice(),
+                rotation.new_signer.as_slice(),
+                rotation.signature.as_bytes().as_slice(),
+            ]);
+        }
+
+        if let CommitmentMode::Eip712(domain) = &self.commitment_mode {
+            id = keccak256_combine([
+                id.as_slice(),
+                b"EIP712",
+                domain.chain_id.to_be_bytes().as_slice(),
+                domain.verifying_contract.as_slice(),
+            ]);
+        }
+
+        id
+    }
+
+    /// Returns the commitment signed by the signer of this [`Certificate`],
+    /// according to its [`CommitmentMode`].
+    pub fn signature_commitment(&self) -> Digest {
+        self.commitment_mode.commitment(
+            *self.network_id,
+            self.height,
+            self.prev_local_exit_root,
+            self.new_local_exit_root,
+            self.imported_bridge_exits
+                .iter()
+                .map(|exit| exit.global_index),
+            self.metadata,
+        )
     }
 
     /// Returns the L1 Info Tree leaf count considered for this [`Certificate`].
@@ -364,14 +581,92 @@ impl Certificate {
         }
     }
 
+    /// Checks the consistency of this [`Certificate`] on its own, without the
+    /// state of its network.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        // The proof checks the token info of every exported bridge exit, messages
+        // included, while the token info of an imported message refers to its sender
+        let check_token_info = |bridge_exit: &BridgeExit, exported: bool| {
+            let token_info = bridge_exit.token_info;
+            if (exported || bridge_exit.leaf_type == LeafType::Transfer)
+                && token_info.origin_token_address == L1_ETH.origin_token_address
+                && token_info != L1_ETH
+            {
+                return Err(ValidationError::InvalidL1EthTokenInfo(token_info));
+            }
+            Ok(())
+        };
+
+        let check_message_origin = |bridge_exit: &BridgeExit, expected: u32| {
+            let declared = bridge_exit.token_info.origin_network;
+            if bridge_exit.leaf_type == LeafType::Message && declared != expected {
+                return Err(ValidationError::InvalidMessageOrigin {
+                    exit: bridge_exit.hash(),
+                    declared,
+                    expected,
+                });
+            }
+            Ok(())
+        };
+
+        for bridge_exit in &self.bridge_exits {
+            if bridge_exit.dest_network == self.network_id {
+                return Err(ValidationError::BridgeExitToSelf(bridge_exit.hash()));
+            }
+            check_message_origin(bridge_exit, *self.network_id)?;
+            check_token_info(bridge_exit, true)?;
+        }
+
+        let mut global_indices = BTreeSet::new();
+        for imported_bridge_exit in &self.imported_bridge_exits {
+            let global_index = imported_bridge_exit.global_index;
+            if !global_indices.insert(global_index) {
+                return Err(ValidationError::DuplicateGlobalIndex(global_index));
+            }
+            if global_index.network_id() == *self.network_id {
+                return Err(ValidationError::ImportedExitFromSelf(global_index));
+            }
+
+            let bridge_exit = &imported_bridge_exit.bridge_exit;
+            if bridge_exit.dest_network != self.network_id {
+                return Err(ValidationError::InvalidImportedExitDestination {
+                    global_index,
+                    dest_network: bridge_exit.dest_network,
+                });
+            }
+            check_message_origin(bridge_exit, global_index.network_id())?;
+            check_token_info(bridge_exit, false)?;
+        }
+
+        self.l1_info_root()
+            .map_err(|_| ValidationError::MultipleL1InfoRoot)?;
+
+        Ok(())
+    }
+
+    /// Signs the commitment of this [`Certificate`] with the given signer,
+    /// according to its [`CommitmentMode`].
+    pub fn sign(&mut self, signer: &impl CertificateSigner) -> Result<(), SignerError> {
+        self.signature = signer.sign_hash(self.signature_commitment())?;
+        Ok(())
+    }
+
+    /// Verifies that this [`Certificate`] is signed by the expected signer.
+    pub fn verify_signature(&self, expected: Address) -> Result<(), Error> {
+        let recovered = self.signer().ok_or(Error::InvalidSignature)?;
+        if recovered != expected {
+            return Err(Error::InvalidSigner {
+                expected,
+                recovered,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn signer(&self) -> Option<Address> {
         // retrieve signer
-        let combined_hash = signature_commitment(
-            self.new_local_exit_root,
-            self.imported_bridge_exits
-                .iter()
-                .map(|exit| exit.global_index),
-        );
+        let combined_hash = self.signature_commitment();
 
         self.signature
             .recover_address_from_prehash(&B256::new(combined_hash.0))
@@ -389,6 +684,37 @@ pub struct LocalNetworkStateData {
     pub balance_tree: Smt<Keccak256Hasher, LOCAL_BALANCE_TREE_DEPTH>,
     /// The full nullifier tree.
     pub nullifier_tree: Smt<Keccak256Hasher, NULLIFIER_TREE_DEPTH>,
+    /// The full outflow limits tree. None if the outflows are not capped.
+    #[serde(default)]
+    pub outflow_limits_tree: Option<Smt<Keccak256Hasher, OUTFLOW_LIMITS_TREE_DEPTH>>,
+    /// The full native supply ledger. None if the native supply is not
+    /// tracked.
+    #[serde(default)]
+    pub native_supply: Option<NativeSupplyLedger>,
+    /// The forced withdrawals pending while the network is frozen, as bridge
+    /// exit hashes. None if the network is not frozen.
+    #[serde(default)]
+    pub forced_withdrawals: Option<Vec<Digest>>,
+    /// The signers authorized to sign the certificates. None if any signer is
+    /// accepted.
+    #[serde(default)]
+    pub authorized_signers: Option<Vec<Address>>,
+    /// The number of changes of the authorized signers so far, committed
+    /// along with them so that a signer rotation cannot be replayed.
+    #[serde(default)]
+    pub signer_set_nonce: u64,
+    /// The domain of the EIP-712 signatures of the certificates. None if the
+    /// legacy commitment is signed.
+    #[serde(default)]
+    pub eip712_domain: Option<Eip712Domain>,
+    /// The minimum timestamp of the L1 info tree leaves referred to by the
+    /// imported bridge exits. None if unconstrained.
+    #[serde(default)]
+    pub min_l1_info_timestamp: Option<u64>,
+    /// The configuration committed by the last proven state transition. The
+    /// configuration set since then is committed by the next one.
+    #[serde(default)]
+    pub proven_config: NetworkConfig,
 }
 
 impl From<LocalNetworkStateData> for LocalNetworkState {
@@ -397,6 +723,18 @@ impl From<LocalNetworkStateData> for LocalNetworkState {
             exit_tree: state.exit_tree,
             balance_tree: LocalBalanceTree::new_with_root(state.balance_tree.root),
             nullifier_tree: NullifierTree::new_with_root(state.nullifier_tree.root),
+            outflow_limits_tree: state
+                .proven_config
+                .outflow_limits_root
+                .map(|root| OutflowLimitsTree { root }),
+            native_supply_tree: state
+                .native_supply
+                .as_ref()
+                .filter(|_| state.proven_config.native_supply_tracked)
+                .map(NativeSupplyTree::from),
+            forced_withdrawals_root: state.proven_config.forced_withdrawals_root,
+            signer_set_root: state.proven_config.signer_set_root,
+            eip712_domain: state.proven_config.eip712_domain,
         }
     }
 }
@@ -412,20 +750,262 @@ impl LocalNetworkStateData {
     pub fn prune_stale_nodes(&mut self) -> Result<(), Error> {
         self.balance_tree.traverse_and_prune()?;
         self.nullifier_tree.traverse_and_prune()?;
+        if let Some(outflow_limits_tree) = &mut self.outflow_limits_tree {
+            outflow_limits_tree.traverse_and_prune()?;
+        }
+        if let Some(native_supply) = &mut self.native_supply {
+            native_supply.tree.traverse_and_prune()?;
+        }
 
         Ok(())
     }
 
+    /// Returns the configuration set by the agglayer, committed by the next
+    /// proven state transition.
+    pub fn config(&self) -> NetworkConfig {
+        NetworkConfig {
+            outflow_limits_root: self.outflow_limits_tree.as_ref().map(|tree| tree.root),
+            native_supply_tracked: self.native_supply.is_some(),
+            forced_withdrawals_root: self.forced_withdrawals.as_ref().map(|forced_withdrawals| {
+                commit_forced_withdrawals(forced_withdrawals.iter().copied())
+            }),
+            signer_set_root: self.signer_set_root(),
+            eip712_domain: self.eip712_domain,
+        }
+    }
+
+    /// Returns the configuration set since the last proven state transition,
+    /// which the next [`MultiBatchHeader`] carries. None if unchanged.
+    pub fn pending_config_update(&self) -> Option<NetworkConfig> {
+        let config = self.config();
+        (config != self.proven_config).then_some(config)
+    }
+
+    /// Sets the per-certificate outflow limit of the given token, and enables
+    /// the outflow limits if they were not already. A zero limit leaves the
+    /// token uncapped. Takes effect with the next certificate, the pessimistic
+    /// root remaining the last proven one until then.
+    pub fn set_outflow_limit(&mut self, token: TokenInfo, limit: U256) -> Result<(), Error> {
+        self.outflow_limits_tree
+            .get_or_insert_with(Smt::new)
+            .update(token, limit.to_be_bytes().into())?;
+
+        Ok(())
+    }
+
+    /// Removes the outflow limit of the given token, leaving it uncapped.
+    /// Takes effect with the next certificate.
+    pub fn remove_outflow_limit(&mut self, token: TokenInfo) -> Result<(), Error> {
+        if let Some(outflow_limits_tree) = &mut self.outflow_limits_tree {
+            outflow_limits_tree.update(token, Digest::default())?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the outflow limit of the given token, if capped.
+    pub fn outflow_limit(&self, token: TokenInfo) -> Option<U256> {
+        self.outflow_limits_tree
+            .as_ref()?
+            .get(token)
+            .map(|limit| U256::from_be_bytes(*limit))
+            .filter(|limit| !limit.is_zero())
+    }
+
+    /// Disables the outflow limits, dropping all the configured caps. Takes
+    /// effect with the next certificate.
+    pub fn disable_outflow_limits(&mut self) {
+        self.outflow_limits_tree = None;
+    }
+
+    /// Starts tracking the native supply, from zero for every native token.
+    /// Does nothing if the native supply is already tracked. Takes effect with
+    /// the next certificate, the pessimistic root remaining the last proven
+    /// one until then.
+    pub fn enable_native_supply_tracking(&mut self) {
+        self.native_supply
+            .get_or_insert_with(NativeSupplyLedger::new);
+    }
+
+    /// Returns the native supply of the given token, if tracked.
+    pub fn native_supply(&self, token: TokenInfo) -> Option<NativeSupply> {
+        Some(self.native_supply.as_ref()?.get(token))
+    }
+
+    /// Freezes the network. Until unfrozen, the network can only import bridge
+    /// exits, and export the given forced withdrawals, each once. Takes effect
+    /// with the next certificate, the pessimistic root remaining the last
+    /// proven one until then.
+    pub fn freeze(
+        &mut self,
+        forced_withdrawals: impl IntoIterator<Item = BridgeExit>,
+    ) -> Result<(), Error> {
+        if self.is_frozen() {
+            return Err(Error::AlreadyFrozen);
+        }
+
+        self.forced_withdrawals = Some(
+            forced_withdrawals
+                .into_iter()
+                .map(|bridge_exit| bridge_exit.hash())
+                .collect(),
+        );
+
+        Ok(())
+    }
+
+    /// Allows the given bridge exit to leave the frozen network. Takes effect
+    /// with the next certificate.
+    pub fn add_forced_withdrawal(&mut self, bridge_exit: &BridgeExit) -> Result<(), Error> {
+        self.forced_withdrawals
+            .as_mut()
+            .ok_or(Error::NotFrozen)?
+            .push(bridge_exit.hash());
+
+        Ok(())
+    }
+
+    /// Unfreezes the network. Returns the forced withdrawals which were not
+    /// withdrawn. Takes effect with the next certificate.
+    pub fn unfreeze(&mut self) -> Result<Vec<Digest>, Error> {
+        self.forced_withdrawals.take().ok_or(Error::NotFrozen)
+    }
+
+    /// Returns whether the network is frozen, as of the next certificate.
+    pub fn is_frozen(&self) -> bool {
+        self.forced_withdrawals.is_some()
+    }
+
+    /// Returns the consensus hash under which the next state transition signed
+    /// by the given signer is proven, which differs while the network is
+    /// frozen.
+    pub fn consensus_hash(&self, signer: Address) -> Digest {
+        pessimistic_proof::proof::consensus_hash(self.is_frozen(), signer)
+    }
+
+    /// Restricts the signers of the certificates to the given set. Takes
+    /// effect with the next certificate, the pessimistic root remaining the
+    /// last proven one until then.
+    pub fn set_authorized_signers(&mut self, signers: impl IntoIterator<Item = Address>) {
+        self.authorized_signers = Some(signers.into_iter().collect());
+        self.signer_set_nonce += 1;
+    }
+
+    /// Accepts the certificates from any signer. Takes effect with the next
+    /// certificate.
+    pub fn clear_authorized_signers(&mut self) {
+        self.authorized_signers = None;
+    }
+
+    /// Returns the authorized signers, None if any signer is accepted, as of
+    /// the next certificate.
+    pub fn authorized_signers(&self) -> Option<&[Address]> {
+        self.authorized_signers.as_deref()
+    }
+
+    /// Returns the commitment to the authorized signers, if restricted, as of
+    /// the next certificate.
+    pub fn signer_set_root(&self) -> Option<Digest> {
+        self.authorized_signers
+            .as_ref()
+            .map(|signers| commit_signer_set(self.signer_set_nonce, signers.iter().copied()))
+    }
+
+    /// Requires the certificates to be signed as EIP-712 typed data within the
+    /// given domain, or with the legacy commitment if None. Takes effect with
+    /// the next certificate, the pessimistic root remaining the last proven
+    /// one until then.
+    pub fn set_eip712_domain(&mut self, domain: Option<Eip712Domain>) {
+        self.eip712_domain = domain;
+    }
+
+    /// Returns the commitment mode which the certificates have to be signed
+    /// with, as of the next certificate.
+    pub fn commitment_mode(&self) -> CommitmentMode {
+        self.eip712_domain
+            .map_or(CommitmentMode::Legacy, CommitmentMode::Eip712)
+    }
+
+    /// Rejects the imported bridge exits referring to L1 info tree leaves
+    /// older than the given timestamp, if any.
+    pub fn set_min_l1_info_timestamp(&mut self, min_timestamp: Option<u64>) {
+        self.min_l1_info_timestamp = min_timestamp;
+    }
+
     /// Apply the [`Certificate`] on the current state and returns the
     /// [`MultiBatchHeader`] associated to the state transition.
+    /// The state isn't modified on error.
     pub fn apply_certificate(
         &mut self,
         certificate: &Certificate,
         signer: Address,
         l1_info_root: Digest,
+    ) -> Result<MultiBatchHeader<Keccak256Hasher>, Error> {
+        let mut clone = self.clone();
+        let multi_batch_header =
+            clone.apply_certificate_helper(certificate, signer, l1_info_root)?;
+        *self = clone;
+
+        Ok(multi_batch_header)
+    }
+
+    /// Apply the [`Certificate`] on the current state and returns the
+    /// [`MultiBatchHeader`] associated to the state transition.
+    /// The state can be modified on error.
+    fn apply_certificate_helper(
+        &mut self,
+        certificate: &Certificate,
+        signer: Address,
+        l1_info_root: Digest,
     ) -> Result<MultiBatchHeader<Keccak256Hasher>, Error> {
         let prev_balance_root = self.balance_tree.root;
         let prev_nullifier_root = self.nullifier_tree.root;
+        let config_update = self.pending_config_update();
+
+        // The certificate has to be signed with the commitment mode of the network
+        let expected = self.commitment_mode();
+        if certificate.commitment_mode != expected {
+            return Err(Error::InvalidCommitmentMode {
+                declared: certificate.commitment_mode,
+                expected,
+            });
+        }
+
+        // The signer has to be authorized, after the optional signer rotation
+        let authorized_signers = self.authorized_signers.clone().unwrap_or_default();
+        let signer_set_nonce = self.signer_set_nonce;
+        match &mut self.authorized_signers {
+            Some(signers) => {
+                (self.signer_set_nonce, *signers) = apply_signer_rotation(
+                    *certificate.network_id,
+                    signer_set_nonce,
+                    signers.clone(),
+                    certificate.signer_rotation.as_ref(),
+                    signer,
+                )
+                .map_err(|error| match error {
+                    ProofError::UnauthorizedSigner(signer) => Error::UnauthorizedSigner(signer),
+                    _ => Error::InvalidSignerRotation,
+                })?;
+            }
+            None if certificate.signer_rotation.is_some() => {
+                return Err(Error::UnexpectedSignerRotation);
+            }
+            None => {}
+        }
+
+        // While frozen, only the forced withdrawals can leave the network, once each
+        let forced_withdrawals = self.forced_withdrawals.clone().unwrap_or_default();
+        if let Some(pending) = &mut self.forced_withdrawals {
+            for bridge_exit in &certificate.bridge_exits {
+                let hash = bridge_exit.hash();
+                let index = pending
+                    .iter()
+                    .position(|pending_hash| *pending_hash == hash)
+                    .ok_or(Error::BridgeExitWhileFrozen(hash))?;
+                pending.remove(index);
+            }
+        }
 
         for e in certificate.bridge_exits.iter() {
             self.exit_tree.add_leaf(e.hash())?;
@@ -507,6 +1087,102 @@ impl LocalNetworkStateData {
                 .collect::<Result<BTreeMap<_, _>, Error>>()?
         };
 
+        let outflow_limits_proofs: BTreeMap<TokenInfo, (U256, OutflowLimitPath<Keccak256Hasher>)> =
+            match &mut self.outflow_limits_tree {
+                None => BTreeMap::new(),
+                Some(outflow_limits_tree) => {
+                    let mut outflows: BTreeMap<TokenInfo, U256> = BTreeMap::new();
+                    for bridge_exit in &certificate.bridge_exits {
+                        let token = bridge_exit.amount_token_info();
+                        let outflow = outflows.entry(token).or_default();
+                        *outflow = outflow
+                            .checked_add(bridge_exit.amount)
+                            .ok_or(Error::OutflowOverflow(token))?;
+                    }
+
+                    outflows
+                        .into_iter()
+                        .map(|(token, outflow)| {
+                            let limit = outflow_limits_tree
+                                .get(token)
+                                .map(|limit| U256::from_be_bytes(*limit))
+                                .unwrap_or_default();
+
+                            if !limit.is_zero() && outflow > limit {
+                                return Err(Error::OutflowLimitExceeded {
+                                    token,
+                                    limit,
+                                    outflow,
+                                });
+                            }
+
+                            let outflow_limit_error =
+                                |source| Error::OutflowLimitProofGenerationFailed { source, token };
+
+                            let path = if limit.is_zero() {
+                                outflow_limits_tree
+                                    .get_inclusion_proof_zero(token)
+                                    .map_err(outflow_limit_error)?
+                            } else {
+                                outflow_limits_tree
+                                    .get_inclusion_proof(token)
+                                    .map_err(outflow_limit_error)?
+                            };
+
+                            Ok((token, (limit, path)))
+                        })
+                        .collect::<Result<BTreeMap<_, _>, Error>>()?
+                }
+            };
+
+        let native_supply_proofs: BTreeMap<
+            TokenInfo,
+            (NativeSupply, NativeSupplyPath<Keccak256Hasher>),
+        > = match &mut self.native_supply {
+            None => BTreeMap::new(),
+            Some(native_supply) => {
+                let network_id = *certificate.network_id;
+
+                // Native amounts bridged in and out in the transition
+                let mut flows: BTreeMap<TokenInfo, NativeSupply> = BTreeMap::new();
+                for imported_bridge_exit in &certificate.imported_bridge_exits {
+                    let token = imported_bridge_exit.bridge_exit.amount_token_info();
+                    if token.origin_network == network_id {
+                        let flow = flows.entry(token).or_default();
+                        flow.imported = flow
+                            .imported
+                            .checked_add(imported_bridge_exit.bridge_exit.amount)
+                            .ok_or(Error::NativeSupplyOverflow(token))?;
+                    }
+                }
+                for bridge_exit in &certificate.bridge_exits {
+                    let token = bridge_exit.amount_token_info();
+                    if token.origin_network == network_id {
+                        let flow = flows.entry(token).or_default();
+                        flow.exported = flow
+                            .exported
+                            .checked_add(bridge_exit.amount)
+                            .ok_or(Error::NativeSupplyOverflow(token))?;
+                    }
+                }
+
+                flows
+                    .into_iter()
+                    .map(|(token, flow)| {
+                        let new_supply = native_supply
+                            .get(token)
+                            .checked_add(&flow)
+                            .ok_or(Error::NativeSupplyOverflow(token))?;
+                        let proof = native_supply.update(token, new_supply).map_err(|source| {
+                            Error::NativeSupplyProofGenerationFailed { source, token }
+                        })?;
+
+                        Ok((token, proof))
+                    })
+                    .collect::<Result<BTreeMap<_, _>, Error>>()?
+            }
+        };
+
         let imported_bridge_exits: Vec<(ImportedBridgeExit, NullifierPath<Keccak256Hasher>)> =
             certificate
                 .imported_bridge_exits
@@ -543,8 +1219,12 @@ impl LocalNetworkStateData {
             });
         }
 
+        // The configuration is committed along with the transition
+        self.proven_config = self.config();
+
         Ok(MultiBatchHeader::<Keccak256Hasher> {
             origin_network: *certificate.network_id,
+            height: certificate.height,
             prev_local_exit_root: certificate.prev_local_exit_root,
             bridge_exits: certificate
                 .bridge_exits
@@ -557,13 +1237,24 @@ impl LocalNetworkStateData {
                 .map(|(ib, ex)| (ib.into(), ex))
                 .collect(),
             balances_proofs,
+            outflow_limits_proofs,
+            native_supply_proofs,
+            forced_withdrawals,
             prev_balance_root,
             prev_nullifier_root,
             signer,
             signature: certificate.signature,
+            commitment_mode: certificate.commitment_mode,
+            metadata: certificate.metadata,
+            authorized_signers,
+            signer_set_nonce,
+            signer_rotation: certificate.signer_rotation.clone(),
             imported_exits_root: Some(imported_hash),
             target: self.get_roots().into(),
             l1_info_root,
+            l1_info_tree_leaf_count: certificate.l1_info_tree_leaf_count().unwrap_or_default(),
+            min_l1_info_timestamp: self.min_l1_info_timestamp,
+            config_update,
         })
     }
 
@@ -576,7 +1267,20 @@ impl LocalNetworkStateData {
         l1_info_root: Digest,
     ) -> Result<MultiBatchHeader<Keccak256Hasher>, Error> {
         self.clone()
-            .apply_certificate(certificate, signer, l1_info_root)
+            .apply_certificate_helper(certificate, signer, l1_info_root)
+    }
+
+    /// Returns the [`Witness`] proving the transition of the given
+    /// [`Certificate`] from the current state, which can be saved and proven
+    /// again later. The state isn't modified.
+    pub fn make_witness(
+        &self,
+        certificate: &Certificate,
+        signer: Address,
+        l1_info_root: Digest,
+    ) -> Result<Witness, Error> {
+        let batch_header = self.make_multi_batch_header(certificate, signer, l1_info_root)?;
+        Ok(Witness::new(self.clone().into(), batch_header))
     }
 
     pub fn get_roots(&self) -> StateCommitment {
@@ -585,6 +1289,1035 @@ impl LocalNetworkStateData {
             ler_leaf_count: self.exit_tree.leaf_count(),
             balance_root: self.balance_tree.root,
             nullifier_root: self.nullifier_tree.root,
+            outflow_limits_root: self.proven_config.outflow_limits_root,
+            native_supply_root: self
+                .native_supply
+                .as_ref()
+                .filter(|_| self.proven_config.native_supply_tracked)
+                .map(NativeSupplyLedger::root),
+            forced_withdrawals_root: self.proven_config.forced_withdrawals_root,
+            signer_set_root: self.proven_config.signer_set_root,
+            eip712_domain_separator: self
+                .proven_config
+                .eip712_domain
+                .as_ref()
+                .map(domain_separator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pessimistic_proof::{
+        bridge_exit::{commit_message_exits, BridgeMessage},
+        core::generate_pessimistic_proof,
+        eip712::Eip712Domain,
+        imported_bridge_exit::{
+            self, Claim, ClaimFromMainnet, L1InfoTreeLeaf, L1InfoTreeLeafInner, MerkleProof,
+        },
+        local_exit_tree::data::LocalExitTreeData,
+        witness::{WitnessError, WitnessFormat, WITNESS_MAGIC},
+        PessimisticProofOutput,
+    };
+    use rand::random;
+
+    use super::*;
+
+    const NETWORK: u32 = 1;
+
+    fn token() -> TokenInfo {
+        TokenInfo {
+            origin_network: 0,
+            origin_token_address: Address::new([0x11; 20]),
+        }
+    }
+
+    fn native_token() -> TokenInfo {
+        TokenInfo {
+            origin_network: NETWORK,
+            origin_token_address: Address::new([0x22; 20]),
+        }
+    }
+
+    fn exit(amount: u64) -> BridgeExit {
+        exit_of(token(), amount)
+    }
+
+    fn exit_of(token: TokenInfo, amount: u64) -> BridgeExit {
+        BridgeExit::new(
+            LeafType::Transfer,
+            token.origin_network.into(),
+            token.origin_token_address,
+            0.into(),
+            random::<[u8; 20]>().into(),
+            U256::from(amount),
+            vec![],
+        )
+    }
+
+    fn state_with_balance(balance: u64) -> LocalNetworkStateData {
+        let mut state = LocalNetworkStateData::default();
+        state
+            .balance_tree
+            .insert(token(), U256::from(balance).to_be_bytes().into())
+            .unwrap();
+        state
+    }
+
+    fn certificate(state: &LocalNetworkStateData, bridge_exits: Vec<BridgeExit>) -> Certificate {
+        let network_id = NETWORK.into();
+        let mut exit_tree = state.exit_tree.clone();
+        for bridge_exit in &bridge_exits {
+            exit_tree.add_leaf(bridge_exit.hash()).unwrap();
+        }
+        let wallet = Certificate::wallet_for_test(network_id);
+        let (_, signature) = compute_signature_info(exit_tree.get_root(), &[], &wallet);
+
+        Certificate {
+            network_id,
+            height: 0,
+            prev_local_exit_root: state.exit_tree.get_root(),
+            new_local_exit_root: exit_tree.get_root(),
+            bridge_exits,
+            imported_bridge_exits: vec![],
+            signature,
+            metadata: Default::default(),
+            signer_rotation: None,
+            commitment_mode: Default::default(),
+        }
+    }
+
+    fn prove(
+        state: &LocalNetworkStateData,
+        header: &MultiBatchHeader<Keccak256Hasher>,
+    ) -> Result<PessimisticProofOutput, ProofError> {
+        generate_pessimistic_proof(state.clone().into(), header)
+    }
+
+    #[test]
+    fn outflow_limit_management() {
+        let mut state = LocalNetworkStateData::default();
+        assert_eq!(state.outflow_limit(token()), None);
+
+        state.set_outflow_limit(token(), U256::from(10)).unwrap();
+        assert_eq!(state.outflow_limit(token()), Some(U256::from(10)));
+
+        state.remove_outflow_limit(token()).unwrap();
+        assert_eq!(state.outflow_limit(token()), None);
+        assert_eq!(
+            state.config().outflow_limits_root,
+            Some(Smt::<Keccak256Hasher, OUTFLOW_LIMITS_TREE_DEPTH>::new().root)
+        );
+
+        state.disable_outflow_limits();
+        assert_eq!(state.config().outflow_limits_root, None);
+        assert_eq!(state.pending_config_update(), None);
+    }
+
+    #[test]
+    fn outflow_limits_take_effect_with_the_next_certificate() {
+        let mut state = state_with_balance(200);
+        let proven_root = state.get_roots().pessimistic_root();
+
+        // The pessimistic root stays the last proven one
+        state.set_outflow_limit(token(), U256::from(50)).unwrap();
+        assert_eq!(state.get_roots().pessimistic_root(), proven_root);
+        let config = state.pending_config_update().unwrap();
+        assert_eq!(
+            config.outflow_limits_root,
+            Some(state.outflow_limits_tree.as_ref().unwrap().root)
+        );
+
+        // The next certificate commits the limits, and is bound by them
+        let within_limit = certificate(&state, vec![exit(20), exit(30)]);
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&within_limit, within_limit.get_signer(), Digest::default())
+            .unwrap();
+        let output = prove(&initial_state, &header).unwrap();
+        assert_eq!(output.prev_pessimistic_root, proven_root);
+        assert_eq!(output.config_update, Some(config.clone()));
+        assert_eq!(
+            output.new_pessimistic_root,
+            state.get_roots().pessimistic_root()
+        );
+        assert_eq!(
+            state.get_roots().outflow_limits_root,
+            config.outflow_limits_root
+        );
+        assert_eq!(state.pending_config_update(), None);
+
+        let above_limit = certificate(&state, vec![exit(60)]);
+        assert!(matches!(
+            state.make_multi_batch_header(
+                &above_limit,
+                above_limit.get_signer(),
+                Digest::default()
+            ),
+            Err(Error::OutflowLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn witness_file_roundtrip() {
+        let state = state_with_balance(100);
+        let certificate = certificate(&state, vec![exit(20), exit(30)]);
+        let witness = state
+            .make_witness(&certificate, certificate.get_signer(), Digest::default())
+            .unwrap();
+        assert_eq!(state.get_roots(), state_with_balance(100).get_roots());
+        let expected = prove(&state, &witness.batch_header).unwrap();
+
+        for extension in ["bin", "json"] {
+            let path =
+                std::env::temp_dir().join(format!("witness-{}.{extension}", std::process::id()));
+            witness.write(&path).unwrap();
+            let loaded = Witness::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            let output =
+                generate_pessimistic_proof(loaded.initial_state, &loaded.batch_header).unwrap();
+            assert_eq!(output.new_pessimistic_root, expected.new_pessimistic_root);
+            assert_eq!(output.new_local_exit_root, expected.new_local_exit_root);
         }
+
+        let mut binary = witness.to_bytes(WitnessFormat::Binary).unwrap();
+        assert!(binary.starts_with(WITNESS_MAGIC));
+        binary[WITNESS_MAGIC.len()] = 1;
+        assert!(matches!(
+            Witness::from_bytes(&binary),
+            Err(WitnessError::UnsupportedVersion(1))
+        ));
+
+        let json = String::from_utf8(witness.to_bytes(WitnessFormat::Json).unwrap()).unwrap();
+        assert!(matches!(
+            Witness::from_bytes(
+                json.replacen("\"version\": 3", "\"version\": 0", 1)
+                    .as_bytes()
+            ),
+            Err(WitnessError::UnsupportedVersion(0))
+        ));
+    }
+
+    #[test]
+    fn outflow_within_limit() {
+        let mut state = state_with_balance(100);
+        state.set_outflow_limit(token(), U256::from(50)).unwrap();
+        let certificate = certificate(&state, vec![exit(20), exit(30)]);
+
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&certificate, certificate.get_signer(), Digest::default())
+            .unwrap();
+        let output = prove(&initial_state, &header).unwrap();
+
+        assert_eq!(
+            output.new_pessimistic_root,
+            state.get_roots().pessimistic_root()
+        );
+    }
+
+    #[test]
+    fn outflow_above_limit() {
+        let mut state = state_with_balance(100);
+        state.set_outflow_limit(token(), U256::from(40)).unwrap();
+        let certificate = certificate(&state, vec![exit(20), exit(30)]);
+
+        assert_eq!(
+            state
+                .make_multi_batch_header(&certificate, certificate.get_signer(), Digest::default())
+                .unwrap_err(),
+            Error::OutflowLimitExceeded {
+                token: token(),
+                limit: U256::from(40),
+                outflow: U256::from(50),
+            }
+        );
+
+        // Witness generated against a higher limit, but proven with the actual one
+        let mut relaxed = state.clone();
+        relaxed.set_outflow_limit(token(), U256::from(50)).unwrap();
+        let mut header = relaxed
+            .make_multi_batch_header(&certificate, certificate.get_signer(), Digest::default())
+            .unwrap();
+        header.config_update = state.pending_config_update();
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::InvalidOutflowLimitPath
+        );
+
+        let outflow_limits_tree = state.outflow_limits_tree.as_ref().unwrap();
+        let path = outflow_limits_tree.get_inclusion_proof(token()).unwrap();
+        header
+            .outflow_limits_proofs
+            .insert(token(), (U256::from(40), path));
+        header.target.outflow_limits_root = Some(outflow_limits_tree.root);
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::OutflowLimitExceeded {
+                token: token(),
+                limit: U256::from(40),
+                outflow: U256::from(50),
+            }
+        );
+    }
+
+    #[test]
+    fn outflow_overflow() {
+        let mut state = LocalNetworkStateData::default();
+        state.set_outflow_limit(native_token(), U256::MAX).unwrap();
+        let bridge_exits = (0..2)
+            .map(|_| BridgeExit {
+                amount: U256::MAX,
+                ..exit_of(native_token(), 0)
+            })
+            .collect();
+        let certificate = certificate(&state, bridge_exits);
+        let signer = certificate.get_signer();
+
+        assert_eq!(
+            state
+                .make_multi_batch_header(&certificate, signer, Digest::default())
+                .unwrap_err(),
+            Error::OutflowOverflow(native_token())
+        );
+
+        // Witness generated without the caps, but proven with them
+        let mut uncapped = state.clone();
+        uncapped.disable_outflow_limits();
+        let mut header = uncapped
+            .make_multi_batch_header(&certificate, signer, Digest::default())
+            .unwrap();
+        header.config_update = state.pending_config_update();
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::OutflowOverflow(native_token())
+        );
+    }
+
+    #[test]
+    fn native_supply_tracking() {
+        let mut state = LocalNetworkStateData::default();
+        assert_eq!(state.native_supply(native_token()), None);
+        assert_eq!(state.get_roots().native_supply_root, None);
+
+        state.enable_native_supply_tracking();
+        assert_eq!(
+            state.native_supply(native_token()),
+            Some(NativeSupply::default())
+        );
+        assert_eq!(state.get_roots().native_supply_root, None);
+        assert!(state.pending_config_update().unwrap().native_supply_tracked);
+
+        for (amounts, total) in [(vec![20, 30], 50u64), (vec![5], 55)] {
+            let bridge_exits = amounts
+                .into_iter()
+                .map(|amount| exit_of(native_token(), amount))
+                .collect();
+            let certificate = certificate(&state, bridge_exits);
+
+            let initial_state = state.clone();
+            let header = state
+                .apply_certificate(&certificate, certificate.get_signer(), Digest::default())
+                .unwrap();
+            let output = prove(&initial_state, &header).unwrap();
+
+            let supply = state.native_supply(native_token()).unwrap();
+            assert_eq!(supply.exported, U256::from(total));
+            assert_eq!(supply.net_exported(), U256::from(total));
+            assert_eq!(
+                output.new_pessimistic_root,
+                state.get_roots().pessimistic_root()
+            );
+        }
+    }
+
+    #[test]
+    fn native_supply_proof_is_required() {
+        let mut state = LocalNetworkStateData::default();
+        state.enable_native_supply_tracking();
+        let certificate = certificate(&state, vec![exit_of(native_token(), 10)]);
+
+        let mut header = state
+            .make_multi_batch_header(&certificate, certificate.get_signer(), Digest::default())
+            .unwrap();
+        header.native_supply_proofs.clear();
+
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::MissingNativeSupplyProof(native_token())
+        );
+    }
+
+    #[test]
+    fn message_exits_are_committed() {
+        let mut state = LocalNetworkStateData::default();
+        state
+            .balance_tree
+            .insert(L1_ETH, U256::from(100).to_be_bytes().into())
+            .unwrap();
+        state.exit_tree.add_leaf(Digest(random())).unwrap();
+
+        let message_exit = BridgeExit::new_message(
+            NETWORK.into(),
+            Address::new([0x33; 20]),
+            0.into(),
+            Address::new([0x44; 20]),
+            U256::from(10),
+            b"calldata",
+        );
+        let certificate = certificate(&state, vec![message_exit.clone()]);
+
+        let header = state
+            .make_multi_batch_header(&certificate, certificate.get_signer(), Digest::default())
+            .unwrap();
+        let output = prove(&state, &header).unwrap();
+
+        // The message lands right after the leaf already in the local exit tree
+        let expected = commit_message_exits([(1, message_exit.as_message().unwrap())].into_iter());
+        assert_eq!(output.message_exits_root, expected);
+    }
+
+    #[test]
+    fn frozen_network_only_exports_forced_withdrawals() {
+        let mut state = state_with_balance(100);
+        let proven_root = state.get_roots().pessimistic_root();
+        let forced_withdrawal = exit(30);
+        state.freeze([forced_withdrawal.clone()]).unwrap();
+        assert_eq!(state.freeze([]), Err(Error::AlreadyFrozen));
+        assert_eq!(state.get_roots().pessimistic_root(), proven_root);
+
+        // Any other bridge exit is rejected, by the agglayer and by the proof
+        let other_exit = exit(10);
+        let other_certificate = certificate(&state, vec![other_exit.clone()]);
+        let signer = other_certificate.get_signer();
+        assert_eq!(
+            state
+                .make_multi_batch_header(&other_certificate, signer, Digest::default())
+                .unwrap_err(),
+            Error::BridgeExitWhileFrozen(other_exit.hash())
+        );
+
+        let mut unfrozen = state.clone();
+        unfrozen.unfreeze().unwrap();
+        let mut header = unfrozen
+            .make_multi_batch_header(&other_certificate, signer, Digest::default())
+            .unwrap();
+        header.config_update = state.pending_config_update();
+        assert!(matches!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::InvalidForcedWithdrawals { .. }
+        ));
+        header.forced_withdrawals = vec![forced_withdrawal.hash()];
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::BridgeExitWhileFrozen(other_exit.hash())
+        );
+
+        // The forced withdrawal leaves once, under the frozen consensus hash
+        let forced_certificate = certificate(&state, vec![forced_withdrawal.clone()]);
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&forced_certificate, signer, Digest::default())
+            .unwrap();
+        let output = prove(&initial_state, &header).unwrap();
+        assert_eq!(output.prev_pessimistic_root, proven_root);
+        assert_eq!(output.config_update, initial_state.pending_config_update());
+        assert_eq!(output.consensus_hash, initial_state.consensus_hash(signer));
+        assert_ne!(output.consensus_hash, unfrozen.consensus_hash(signer));
+        assert_eq!(
+            output.new_pessimistic_root,
+            state.get_roots().pessimistic_root()
+        );
+
+        let replayed_certificate = certificate(&state, vec![forced_withdrawal.clone()]);
+        assert_eq!(
+            state
+                .make_multi_batch_header(&replayed_certificate, signer, Digest::default())
+                .unwrap_err(),
+            Error::BridgeExitWhileFrozen(forced_withdrawal.hash())
+        );
+
+        // The unfreeze is committed by the next certificate, proven under the
+        // default consensus hash
+        assert_eq!(state.unfreeze(), Ok(vec![]));
+        assert_eq!(state.unfreeze(), Err(Error::NotFrozen));
+        let next_certificate = certificate(&state, vec![exit(10)]);
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&next_certificate, signer, Digest::default())
+            .unwrap();
+        let output = prove(&initial_state, &header).unwrap();
+        assert_eq!(output.consensus_hash, unfrozen.consensus_hash(signer));
+        assert_eq!(output.config_update.unwrap().forced_withdrawals_root, None);
+        assert_eq!(state.get_roots().forced_withdrawals_root, None);
+    }
+
+    #[test]
+    fn signer_rotation_replaces_authorized_signer() {
+        use ethers::signers::Signer as _;
+
+        let old_wallet = Certificate::wallet_for_test(2.into());
+        let old_signer: Address = old_wallet.address().0.into();
+        let mut state = state_with_balance(100);
+        let proven_root = state.get_roots().pessimistic_root();
+        state.set_authorized_signers([old_signer]);
+        assert_eq!(state.get_roots().pessimistic_root(), proven_root);
+
+        // The certificate signer is not authorized yet
+        let mut rotating = certificate(&state, vec![exit(10)]);
+        let new_signer = rotating.get_signer();
+        assert_eq!(
+            state
+                .make_multi_batch_header(&rotating, new_signer, Digest::default())
+                .unwrap_err(),
+            Error::UnauthorizedSigner(new_signer)
+        );
+
+        // The old signer hands over to the certificate signer
+        let rotation = compute_signer_rotation(
+            NETWORK.into(),
+            state.signer_set_root().unwrap(),
+            new_signer,
+            &old_wallet,
+        );
+        rotating.signer_rotation = Some(rotation.clone());
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&rotating, new_signer, Digest::default())
+            .unwrap();
+        let output = prove(&initial_state, &header).unwrap();
+        assert_eq!(output.prev_pessimistic_root, proven_root);
+        assert_eq!(
+            output.config_update.unwrap().signer_set_root,
+            initial_state.signer_set_root()
+        );
+        assert_eq!(output.signer_rotation, Some(rotation.event()));
+        assert_eq!(
+            output.new_pessimistic_root,
+            state.get_roots().pessimistic_root()
+        );
+        assert_eq!(state.authorized_signers(), Some([new_signer].as_slice()));
+
+        // The old signer is no longer authorized, and the rotation cannot be
+        // replayed
+        let mut next = certificate(&state, vec![]);
+        assert_eq!(
+            state
+                .make_multi_batch_header(&next, old_signer, Digest::default())
+                .unwrap_err(),
+            Error::UnauthorizedSigner(old_signer)
+        );
+        next.signer_rotation = Some(rotation);
+        assert_eq!(
+            state
+                .make_multi_batch_header(&next, new_signer, Digest::default())
+                .unwrap_err(),
+            Error::InvalidSignerRotation
+        );
+
+        state.clear_authorized_signers();
+        assert_eq!(
+            state
+                .make_multi_batch_header(&next, new_signer, Digest::default())
+                .unwrap_err(),
+            Error::UnexpectedSignerRotation
+        );
+    }
+
+    #[test]
+    fn signer_rotation_cannot_be_replayed() {
+        use ethers::signers::Signer as _;
+
+        let wallet_a = Certificate::wallet_for_test(2.into());
+        let wallet_b = Certificate::wallet_for_test(NETWORK.into());
+        let signer_a: Address = wallet_a.address().0.into();
+        let signer_b: Address = wallet_b.address().0.into();
+        let mut state = state_with_balance(100);
+        state.set_authorized_signers([signer_a]);
+
+        let signed_by = |state: &LocalNetworkStateData, wallet| {
+            let mut certificate = certificate(state, vec![]);
+            let (_, signature) =
+                compute_signature_info(certificate.new_local_exit_root, &[], wallet);
+            certificate.signature = signature;
+            certificate
+        };
+
+        // A hands over to B, then B hands back over to A
+        let a_to_b = compute_signer_rotation(
+            NETWORK.into(),
+            state.signer_set_root().unwrap(),
+            signer_b,
+            &wallet_a,
+        );
+        let mut certificate = signed_by(&state, &wallet_b);
+        certificate.signer_rotation = Some(a_to_b.clone());
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&certificate, signer_b, Digest::default())
+            .unwrap();
+        prove(&initial_state, &header).unwrap();
+
+        let b_to_a = compute_signer_rotation(
+            NETWORK.into(),
+            state.signer_set_root().unwrap(),
+            signer_a,
+            &wallet_b,
+        );
+        let mut certificate = signed_by(&state, &wallet_a);
+        certificate.signer_rotation = Some(b_to_a);
+        let initial_state = state.clone();
+        let header = state
+            .apply_certificate(&certificate, signer_a, Digest::default())
+            .unwrap();
+        prove(&initial_state, &header).unwrap();
+        assert_eq!(state.authorized_signers(), Some([signer_a].as_slice()));
+        assert_eq!(state.signer_set_nonce, 3);
+
+        // The archived rotation from A to B is rejected, by the agglayer and by
+        // the proof
+        let mut replayed = signed_by(&state, &wallet_b);
+        replayed.signer_rotation = Some(a_to_b.clone());
+        assert_eq!(
+            state
+                .make_multi_batch_header(&replayed, signer_b, Digest::default())
+                .unwrap_err(),
+            Error::InvalidSignerRotation
+        );
+
+        let mut header = state
+            .make_multi_batch_header(&signed_by(&state, &wallet_a), signer_a, Digest::default())
+            .unwrap();
+        header.signer_rotation = Some(a_to_b);
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::InvalidSignerRotation
+        );
+    }
+
+    #[test]
+    fn failing_certificate_leaves_state_unchanged() {
+        use ethers::signers::Signer as _;
+
+        let old_wallet = Certificate::wallet_for_test(2.into());
+        let old_signer: Address = old_wallet.address().0.into();
+        let mut state = state_with_balance(20);
+        state.set_authorized_signers([old_signer]);
+        let forced_withdrawal = exit(30);
+        state.freeze([forced_withdrawal.clone()]).unwrap();
+
+        // The rotation and the forced withdrawal are valid, but the balance
+        // underflows afterwards
+        let mut certificate = certificate(&state, vec![forced_withdrawal.clone()]);
+        let signer = certificate.get_signer();
+        certificate.signer_rotation = Some(compute_signer_rotation(
+            NETWORK.into(),
+            state.signer_set_root().unwrap(),
+            signer,
+            &old_wallet,
+        ));
+
+        let roots = state.get_roots();
+        assert_eq!(
+            state
+                .apply_certificate(&certificate, signer, Digest::default())
+                .unwrap_err(),
+            Error::BalanceUnderflow(token())
+        );
+        assert_eq!(state.get_roots(), roots);
+        assert_eq!(state.authorized_signers(), Some([old_signer].as_slice()));
+        assert_eq!(
+            state.forced_withdrawals,
+            Some(vec![forced_withdrawal.hash()])
+        );
+    }
+
+    fn eip712_domain() -> Eip712Domain {
+        Eip712Domain {
+            chain_id: 1,
+            verifying_contract: Address::new([0x55; 20]),
+        }
+    }
+
+    #[test]
+    fn eip712_signing_hash_matches_ethers() {
+        use ethers::abi::{encode, Token};
+        use ethers::types::transaction::eip712::EIP712Domain;
+        use ethers::utils::keccak256;
+
+        let mut certificate = certificate(&LocalNetworkStateData::default(), vec![exit(10)]);
+        certificate.height = random::<u32>() as u64;
+        certificate.metadata = Digest(random());
+
+        let domain = EIP712Domain {
+            name: Some("AggLayer".to_string()),
+            version: Some("1".to_string()),
+            chain_id: Some(eip712_domain().chain_id.into()),
+            verifying_contract: Some(eip712_domain().verifying_contract.0 .0.into()),
+            salt: None,
+        };
+        let struct_hash = keccak256(encode(&[
+            Token::FixedBytes(
+                keccak256(
+                    "Certificate(uint32 networkId,uint64 height,bytes32 prevLocalExitRoot,\
+                     bytes32 newLocalExitRoot,bytes32 importedBridgeExitsHash,bytes32 metadata)",
+                )
+                .to_vec(),
+            ),
+            Token::Uint((*certificate.network_id).into()),
+            Token::Uint(certificate.height.into()),
+            Token::FixedBytes(certificate.prev_local_exit_root.0.to_vec()),
+            Token::FixedBytes(certificate.new_local_exit_root.0.to_vec()),
+            Token::FixedBytes(commit_imported_bridge_exits([].into_iter()).0.to_vec()),
+            Token::FixedBytes(certificate.metadata.0.to_vec()),
+        ]));
+        let expected =
+            keccak256([[0x19, 0x01].as_slice(), &domain.separator(), &struct_hash].concat());
+
+        certificate.commitment_mode = CommitmentMode::Eip712(eip712_domain());
+        assert_eq!(certificate.signature_commitment(), Digest(expected));
+
+        // The digest is bound to the chain and to the verifying contract
+        let hash = certificate.hash();
+        for domain in [
+            Eip712Domain {
+                chain_id: 2,
+                ..eip712_domain()
+            },
+            Eip712Domain {
+                verifying_contract: Address::new([0x66; 20]),
+                ..eip712_domain()
+            },
+        ] {
+            let mut other = certificate.clone();
+            other.commitment_mode = CommitmentMode::Eip712(domain);
+            assert_ne!(other.signature_commitment(), Digest(expected));
+            assert_ne!(other.hash(), hash);
+        }
+    }
+
+    #[test]
+    fn eip712_signature_is_verified_by_the_proof() {
+        let mut state = state_with_balance(100);
+        state.set_eip712_domain(Some(eip712_domain()));
+        let mut certificate = certificate(&state, vec![exit(10)]);
+        let signer = certificate.get_signer();
+        let wallet = Certificate::wallet_for_test(certificate.network_id);
+        certificate.commitment_mode = state.commitment_mode();
+        certificate.height = 1;
+        certificate.signature = compute_certificate_signature(&certificate, &wallet);
+        assert_eq!(certificate.signer(), Some(signer));
+
+        let initial_state = state.clone();
+        let mut header = state
+            .apply_certificate(&certificate, signer, Digest::default())
+            .unwrap();
+        let output = prove(&initial_state, &header).unwrap();
+        assert_eq!(
+            output.config_update.unwrap().eip712_domain,
+            Some(eip712_domain())
+        );
+        assert_eq!(
+            output.new_pessimistic_root,
+            state.get_roots().pessimistic_root()
+        );
+
+        // The height is bound by the signature
+        header.height = 2;
+        assert!(matches!(
+            prove(&initial_state, &header).unwrap_err(),
+            ProofError::InvalidSigner { declared, .. } if declared == signer
+        ));
+    }
+
+    #[test]
+    fn eip712_signature_of_another_domain_is_rejected() {
+        let mut state = state_with_balance(100);
+        state.set_eip712_domain(Some(eip712_domain()));
+        let signer = Certificate::wallet_for_test(NETWORK.into());
+        let sign = |mut certificate: Certificate, commitment_mode| {
+            certificate.commitment_mode = commitment_mode;
+            certificate.signature = compute_certificate_signature(&certificate, &signer);
+            certificate
+        };
+
+        // Correctly signed for another deployment, or with the legacy commitment
+        let foreign_domain = Eip712Domain {
+            chain_id: 2,
+            ..eip712_domain()
+        };
+        let base = certificate(&state, vec![exit(10)]);
+        let foreign = sign(base.clone(), CommitmentMode::Eip712(foreign_domain));
+        let legacy = sign(base.clone(), CommitmentMode::Legacy);
+
+        for certificate in [&foreign, &legacy] {
+            assert_eq!(
+                state
+                    .apply_certificate(certificate, certificate.get_signer(), Digest::default())
+                    .unwrap_err(),
+                Error::InvalidCommitmentMode {
+                    declared: certificate.commitment_mode,
+                    expected: CommitmentMode::Eip712(eip712_domain()),
+                }
+            );
+        }
+
+        // The proof rejects them as well, the domain being committed in the state
+        let valid = sign(base, CommitmentMode::Eip712(eip712_domain()));
+        let header = state
+            .make_multi_batch_header(&valid, valid.get_signer(), Digest::default())
+            .unwrap();
+        for certificate in [&foreign, &legacy] {
+            let mut header = header.clone();
+            header.commitment_mode = certificate.commitment_mode;
+            header.signature = certificate.signature;
+            assert_eq!(
+                prove(&state, &header).unwrap_err(),
+                ProofError::InvalidCommitmentMode {
+                    declared: certificate.commitment_mode,
+                    expected: CommitmentMode::Eip712(eip712_domain()),
+                }
+            );
+        }
+
+        // Once proven, the domain remains required
+        let mut state_after = state.clone();
+        state_after
+            .apply_certificate(&valid, valid.get_signer(), Digest::default())
+            .unwrap();
+        let mut next = certificate(&state_after, vec![exit(10)]);
+        next.height = 1;
+        let legacy = sign(next.clone(), CommitmentMode::Legacy);
+        let valid = sign(next, CommitmentMode::Eip712(eip712_domain()));
+        let mut header = state_after
+            .make_multi_batch_header(&valid, valid.get_signer(), Digest::default())
+            .unwrap();
+        assert_eq!(header.config_update, None);
+        header.commitment_mode = legacy.commitment_mode;
+        header.signature = legacy.signature;
+        assert_eq!(
+            prove(&state_after, &header).unwrap_err(),
+            ProofError::InvalidCommitmentMode {
+                declared: CommitmentMode::Legacy,
+                expected: CommitmentMode::Eip712(eip712_domain()),
+            }
+        );
+    }
+
+    fn imported_exit(leaf_index: u32, l1_info_root: Digest) -> ImportedBridgeExit {
+        let mut bridge_exit = exit(10);
+        bridge_exit.dest_network = NETWORK.into();
+        let mut tree = LocalExitTreeData::<Keccak256Hasher>::new();
+        tree.add_leaf(bridge_exit.hash()).unwrap();
+        let proof = MerkleProof {
+            proof: tree.get_proof(0).unwrap(),
+            root: l1_info_root,
+        };
+
+        ImportedBridgeExit::new(
+            bridge_exit,
+            Claim::Mainnet(Box::new(ClaimFromMainnet {
+                proof_leaf_mer: proof.clone(),
+                proof_ger_l1root: proof,
+                l1_leaf: L1InfoTreeLeaf {
+                    l1_info_tree_index: 0,
+                    rer: Digest::default(),
+                    mer: Digest::default(),
+                    inner: L1InfoTreeLeafInner {
+                        global_exit_root: Digest::default(),
+                        block_hash: Digest::default(),
+                        timestamp: 0,
+                    },
+                },
+            })),
+            GlobalIndex {
+                mainnet_flag: true,
+                rollup_index: 0,
+                leaf_index,
+            },
+        )
+    }
+
+    fn importing_certificate(state: &LocalNetworkStateData) -> Certificate {
+        let imported_bridge_exits = vec![imported_exit(0, Digest(random()))];
+        let wallet = Certificate::wallet_for_test(NETWORK.into());
+        let (_, signature) =
+            compute_signature_info(state.exit_tree.get_root(), &imported_bridge_exits, &wallet);
+
+        Certificate {
+            imported_bridge_exits,
+            signature,
+            ..certificate(state, vec![])
+        }
+    }
+
+    #[test]
+    fn imported_exit_beyond_leaf_count_is_rejected_by_the_proof() {
+        let state = state_with_balance(100);
+        let certificate = importing_certificate(&state);
+        let global_index = certificate.imported_bridge_exits[0].global_index;
+        let l1_info_root = certificate.l1_info_root().unwrap().unwrap();
+
+        // The L1 info tree committed by the header does not contain the leaf yet
+        let mut header = state
+            .make_multi_batch_header(&certificate, certificate.get_signer(), l1_info_root)
+            .unwrap();
+        assert_eq!(header.l1_info_tree_leaf_count, 1);
+        header.l1_info_tree_leaf_count = 0;
+
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::InvalidImportedBridgeExit {
+                source: imported_bridge_exit::Error::L1InfoTreeIndexOutOfBounds {
+                    index: 0,
+                    leaf_count: 0,
+                },
+                global_index,
+            }
+        );
+    }
+
+    #[test]
+    fn stale_imported_exit_is_rejected_by_the_proof() {
+        let mut state = state_with_balance(100);
+        let certificate = importing_certificate(&state);
+        let global_index = certificate.imported_bridge_exits[0].global_index;
+        let l1_info_root = certificate.l1_info_root().unwrap().unwrap();
+
+        state.set_min_l1_info_timestamp(Some(1));
+        let header = state
+            .make_multi_batch_header(&certificate, certificate.get_signer(), l1_info_root)
+            .unwrap();
+        assert_eq!(header.min_l1_info_timestamp, Some(1));
+
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::InvalidImportedBridgeExit {
+                source: imported_bridge_exit::Error::StaleL1InfoTreeLeaf {
+                    timestamp: 0,
+                    min_timestamp: 1,
+                },
+                global_index,
+            }
+        );
+    }
+
+    #[test]
+    fn certificate_validation() {
+        let l1_info_root = Digest(random());
+        let mut certificate = certificate(&LocalNetworkStateData::default(), vec![exit(10)]);
+        certificate.imported_bridge_exits = vec![
+            imported_exit(0, l1_info_root),
+            imported_exit(1, l1_info_root),
+        ];
+        assert_eq!(certificate.validate(), Ok(()));
+
+        let mut invalid = certificate.clone();
+        invalid.imported_bridge_exits[1].global_index.leaf_index = 0;
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::DuplicateGlobalIndex(
+                invalid.imported_bridge_exits[0].global_index
+            ))
+        );
+
+        let mut invalid = certificate.clone();
+        invalid.imported_bridge_exits[1].bridge_exit.dest_network = 2.into();
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::InvalidImportedExitDestination {
+                global_index: invalid.imported_bridge_exits[1].global_index,
+                dest_network: 2.into(),
+            })
+        );
+
+        let mut invalid = certificate.clone();
+        invalid.bridge_exits[0].dest_network = NETWORK.into();
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::BridgeExitToSelf(
+                invalid.bridge_exits[0].hash()
+            ))
+        );
+
+        let mut invalid = certificate.clone();
+        invalid.imported_bridge_exits[1] = imported_exit(1, Digest(random()));
+        assert_eq!(invalid.validate(), Err(ValidationError::MultipleL1InfoRoot));
+
+        let mut invalid = certificate.clone();
+        let message = BridgeExit::from(BridgeMessage {
+            origin_network: 2,
+            origin_address: Address::new(random()),
+            dest_network: 0,
+            recipient: Address::new(random()),
+            value: U256::ZERO,
+            calldata_hash: Digest(random()),
+        });
+        invalid.bridge_exits.push(message.clone());
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::InvalidMessageOrigin {
+                exit: message.hash(),
+                declared: 2,
+                expected: NETWORK,
+            })
+        );
+
+        let mut invalid = certificate.clone();
+        let bad_eth = TokenInfo {
+            origin_network: NETWORK,
+            ..L1_ETH
+        };
+        invalid.bridge_exits[0].token_info = bad_eth;
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::InvalidL1EthTokenInfo(bad_eth))
+        );
+
+        let mut invalid = certificate.clone();
+        invalid.imported_bridge_exits[1].global_index = GlobalIndex {
+            mainnet_flag: false,
+            rollup_index: NETWORK - 1,
+            leaf_index: 1,
+        };
+        assert_eq!(
+            invalid.validate(),
+            Err(ValidationError::ImportedExitFromSelf(
+                invalid.imported_bridge_exits[1].global_index
+            ))
+        );
+    }
+
+    #[test]
+    fn exported_message_token_info_is_validated_as_in_the_proof() {
+        let mut state = LocalNetworkStateData::default();
+        state
+            .balance_tree
+            .insert(L1_ETH, U256::from(100).to_be_bytes().into())
+            .unwrap();
+
+        // A message from the zero address of the network
+        let message = BridgeExit::new_message(
+            NETWORK.into(),
+            Address::ZERO,
+            0.into(),
+            Address::new([0x44; 20]),
+            U256::from(10),
+            b"calldata",
+        );
+        let certificate = certificate(&state, vec![message.clone()]);
+        assert_eq!(
+            certificate.validate(),
+            Err(ValidationError::InvalidL1EthTokenInfo(message.token_info))
+        );
+
+        let header = state
+            .make_multi_batch_header(&certificate, certificate.get_signer(), Digest::default())
+            .unwrap();
+        assert_eq!(
+            prove(&state, &header).unwrap_err(),
+            ProofError::InvalidL1TokenInfo(message.token_info)
+        );
     }
 }
@@ -0,0 +1,301 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CertificateId, CertificateStatus};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LifecycleError {
+    /// The certificate is not tracked.
+    #[error("Unknown certificate: {0}")]
+    UnknownCertificate(CertificateId),
+    /// The certificate is already tracked.
+    #[error("Certificate already registered: {0}")]
+    AlreadyRegistered(CertificateId),
+    /// The certificate cannot move from its current status to the requested
+    /// one.
+    #[error("Invalid transition of {certificate_id}. from: {from}, to: {to}")]
+    InvalidTransition {
+        certificate_id: CertificateId,
+        from: Box<CertificateStatus>,
+        to: Box<CertificateStatus>,
+    },
+    /// The store file cannot be read or written.
+    #[error("Lifecycle store I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The store file cannot be decoded.
+    #[error("Lifecycle store serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl CertificateStatus {
+    /// Returns whether a certificate can move from this status to the given
+    /// one. Settled is final, and the certificates in error can be retried
+    /// from Pending.
+    pub fn can_transition_to(&self, next: &CertificateStatus) -> bool {
+        use CertificateStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Proven)
+                | (Proven, Candidate)
+                | (Candidate, Settled)
+                | (Pending | Proven | Candidate, InError { .. })
+                | (InError { .. }, Pending)
+        )
+    }
+}
+
+/// One status taken by a certificate, along with the time at which it was
+/// taken, in seconds since the Unix epoch.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusChange {
+    pub status: CertificateStatus,
+    pub timestamp: u64,
+}
+
+/// Status history of the tracked certificates, only updated through valid
+/// transitions.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CertificateLifecycle {
+    history: BTreeMap<CertificateId, Vec<StatusChange>>,
+}
+
+impl CertificateLifecycle {
+    /// Creates a new lifecycle without any certificate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking the given certificate as Pending.
+    pub fn register(&mut self, certificate_id: CertificateId) -> Result<(), LifecycleError> {
+        if self.history.contains_key(&certificate_id) {
+            return Err(LifecycleError::AlreadyRegistered(certificate_id));
+        }
+
+        self.history.insert(
+            certificate_id,
+            vec![StatusChange {
+                status: CertificateStatus::Pending,
+                timestamp: now(),
+            }],
+        );
+
+        Ok(())
+    }
+
+    /// Moves the given certificate to the given status.
+    pub fn transition(
+        &mut self,
+        certificate_id: CertificateId,
+        status: CertificateStatus,
+    ) -> Result<(), LifecycleError> {
+        let history = self
+            .history
+            .get_mut(&certificate_id)
+            .ok_or(LifecycleError::UnknownCertificate(certificate_id))?;
+
+        // The history of a registered certificate is never empty
+        let current = &history.last().expect("empty certificate history").status;
+        if !current.can_transition_to(&status) {
+            return Err(LifecycleError::InvalidTransition {
+                certificate_id,
+                from: Box::new(current.clone()),
+                to: Box::new(status),
+            });
+        }
+
+        history.push(StatusChange {
+            status,
+            timestamp: now(),
+        });
+
+        Ok(())
+    }
+
+    /// Returns the current status of the given certificate.
+    pub fn status(&self, certificate_id: &CertificateId) -> Option<&CertificateStatus> {
+        self.history(certificate_id)?
+            .last()
+            .map(|change| &change.status)
+    }
+
+    /// Returns all the statuses taken by the given certificate, oldest first.
+    pub fn history(&self, certificate_id: &CertificateId) -> Option<&[StatusChange]> {
+        self.history.get(certificate_id).map(Vec::as_slice)
+    }
+}
+
+/// [`CertificateLifecycle`] persisted as JSON in a local file, which is
+/// rewritten upon every change.
+#[derive(Debug)]
+pub struct LifecycleStore {
+    path: PathBuf,
+    lifecycle: CertificateLifecycle,
+}
+
+impl LifecycleStore {
+    /// Opens the store at the given path, empty if the file does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, LifecycleError> {
+        let path = path.as_ref().to_path_buf();
+        let lifecycle = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Default::default(),
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self { path, lifecycle })
+    }
+
+    /// Returns the persisted lifecycle.
+    pub fn lifecycle(&self) -> &CertificateLifecycle {
+        &self.lifecycle
+    }
+
+    /// Starts tracking the given certificate as Pending, and persists it.
+    pub fn register(&mut self, certificate_id: CertificateId) -> Result<(), LifecycleError> {
+        let mut lifecycle = self.lifecycle.clone();
+        lifecycle.register(certificate_id)?;
+        self.persist(lifecycle)
+    }
+
+    /// Moves the given certificate to the given status, and persists it.
+    pub fn transition(
+        &mut self,
+        certificate_id: CertificateId,
+        status: CertificateStatus,
+    ) -> Result<(), LifecycleError> {
+        let mut lifecycle = self.lifecycle.clone();
+        lifecycle.transition(certificate_id, status)?;
+        self.persist(lifecycle)
+    }
+
+    /// Writes the given lifecycle to a temporary file first, so that the store
+    /// is never left half written, and keeps it in memory only once written.
+    fn persist(&mut self, lifecycle: CertificateLifecycle) -> Result<(), LifecycleError> {
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&lifecycle)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        self.lifecycle = lifecycle;
+
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::random;
+
+    use super::*;
+    use crate::{CertificateStatusError, Digest, Error};
+
+    fn in_error() -> CertificateStatus {
+        CertificateStatus::InError {
+            error: CertificateStatusError::TypeConversionError(Error::MultipleL1InfoRoot),
+        }
+    }
+
+    #[test]
+    fn lifecycle_transitions() {
+        let id = Digest(random());
+        let mut lifecycle = CertificateLifecycle::new();
+        assert!(matches!(
+            lifecycle.transition(id, CertificateStatus::Proven),
+            Err(LifecycleError::UnknownCertificate(_))
+        ));
+
+        lifecycle.register(id).unwrap();
+        assert!(matches!(
+            lifecycle.register(id),
+            Err(LifecycleError::AlreadyRegistered(_))
+        ));
+
+        // Retried once after an error
+        lifecycle.transition(id, in_error()).unwrap();
+        lifecycle
+            .transition(id, CertificateStatus::Pending)
+            .unwrap();
+        for status in [
+            CertificateStatus::Proven,
+            CertificateStatus::Candidate,
+            CertificateStatus::Settled,
+        ] {
+            lifecycle.transition(id, status).unwrap();
+        }
+
+        for status in [CertificateStatus::Pending, in_error()] {
+            assert!(matches!(
+                lifecycle.transition(id, status),
+                Err(LifecycleError::InvalidTransition { .. })
+            ));
+        }
+
+        assert_eq!(lifecycle.status(&id), Some(&CertificateStatus::Settled));
+        let history = lifecycle.history(&id).unwrap();
+        assert_eq!(history.len(), 6);
+        assert!(history
+            .windows(2)
+            .all(|changes| changes[0].timestamp <= changes[1].timestamp));
+    }
+
+    #[test]
+    fn lifecycle_store_persists_history() {
+        let path =
+            std::env::temp_dir().join(format!("agglayer-lifecycle-{}.json", random::<u64>()));
+        let id = Digest(random());
+
+        let mut store = LifecycleStore::open(&path).unwrap();
+        store.register(id).unwrap();
+        store.transition(id, CertificateStatus::Proven).unwrap();
+        assert!(store.transition(id, CertificateStatus::Settled).is_err());
+
+        let reopened = LifecycleStore::open(&path).unwrap();
+        assert_eq!(
+            reopened.lifecycle().history(&id),
+            store.lifecycle().history(&id)
+        );
+        assert_eq!(
+            reopened.lifecycle().status(&id),
+            Some(&CertificateStatus::Proven)
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn lifecycle_store_failed_persist() {
+        let dir = std::env::temp_dir().join(format!("agglayer-lifecycle-{}", random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let id = Digest(random());
+
+        let mut store = LifecycleStore::open(dir.join("lifecycle.json")).unwrap();
+        store.register(id).unwrap();
+
+        // Nothing changes in memory if the store cannot be written
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            store.transition(id, CertificateStatus::Proven),
+            Err(LifecycleError::Io(_))
+        ));
+        assert!(matches!(
+            store.register(Digest(random())),
+            Err(LifecycleError::Io(_))
+        ));
+        assert_eq!(
+            store.lifecycle().status(&id),
+            Some(&CertificateStatus::Pending)
+        );
+        assert_eq!(store.lifecycle().history(&id).unwrap().len(), 1);
+    }
+}
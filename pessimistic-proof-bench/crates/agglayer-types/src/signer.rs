@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use k256::ecdsa::SigningKey;
+
+use crate::{Address, Digest, Signature};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    /// The private key is not a valid secp256k1 scalar.
+    #[error("Invalid private key")]
+    InvalidPrivateKey,
+    /// The keystore file cannot be read or decrypted.
+    #[error("Unable to decrypt the keystore: {0}")]
+    Keystore(#[from] eth_keystore::KeystoreError),
+    /// The hash cannot be signed.
+    #[error("Unable to sign: {0}")]
+    Signing(#[from] k256::ecdsa::Error),
+}
+
+/// Signs the commitments of the [`crate::Certificate`] of one network.
+pub trait CertificateSigner {
+    /// Returns the address of the signer.
+    fn address(&self) -> Address;
+
+    /// Signs the given prehashed commitment.
+    fn sign_hash(&self, hash: Digest) -> Result<Signature, SignerError>;
+}
+
+/// Signer holding its secp256k1 private key in memory.
+#[derive(Clone)]
+pub struct LocalSigner {
+    key: SigningKey,
+    address: Address,
+}
+
+impl LocalSigner {
+    /// Creates the signer from the given private key.
+    pub fn from_bytes(private_key: &[u8]) -> Result<Self, SignerError> {
+        let key =
+            SigningKey::from_slice(private_key).map_err(|_| SignerError::InvalidPrivateKey)?;
+        let address = Address::from_private_key(&key);
+
+        Ok(Self { key, address })
+    }
+
+    /// Creates the signer from a random private key.
+    pub fn random() -> Self {
+        let key = SigningKey::random(&mut rand::thread_rng());
+        let address = Address::from_private_key(&key);
+
+        Self { key, address }
+    }
+}
+
+impl std::fmt::Debug for LocalSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never display the private key
+        f.debug_struct("LocalSigner")
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+impl CertificateSigner for LocalSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign_hash(&self, hash: Digest) -> Result<Signature, SignerError> {
+        let (signature, recovery_id) = self.key.sign_prehash_recoverable(hash.as_slice())?;
+
+        Ok(Signature::from_signature_and_parity(
+            signature,
+            recovery_id.is_y_odd(),
+        ))
+    }
+}
+
+/// Signer whose private key is stored in an encrypted JSON keystore file, as
+/// defined by the Web3 Secret Storage.
+#[derive(Clone, Debug)]
+pub struct KeystoreSigner {
+    inner: LocalSigner,
+}
+
+impl KeystoreSigner {
+    /// Decrypts the private key of the given keystore file.
+    pub fn open(path: impl AsRef<Path>, password: impl AsRef<[u8]>) -> Result<Self, SignerError> {
+        let private_key = eth_keystore::decrypt_key(path, password)?;
+
+        Ok(Self {
+            inner: LocalSigner::from_bytes(&private_key)?,
+        })
+    }
+
+    /// Creates a new random private key, encrypted in a keystore file named
+    /// `name` within the given directory.
+    pub fn create(
+        dir: impl AsRef<Path>,
+        name: &str,
+        password: impl AsRef<[u8]>,
+    ) -> Result<Self, SignerError> {
+        let inner = LocalSigner::random();
+        eth_keystore::encrypt_key(
+            dir,
+            &mut rand::thread_rng(),
+            inner.key.to_bytes(),
+            password,
+            Some(name),
+        )?;
+
+        Ok(Self { inner })
+    }
+}
+
+impl CertificateSigner for KeystoreSigner {
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+
+    fn sign_hash(&self, hash: Digest) -> Result<Signature, SignerError> {
+        self.inner.sign_hash(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::random;
+
+    use super::*;
+    use crate::{Certificate, Error};
+
+    #[test]
+    fn local_signer_signs_certificate() {
+        let network_id = random::<u32>().into();
+        let wallet = Certificate::wallet_for_test(network_id);
+        let signer = LocalSigner::from_bytes(&wallet.signer().to_bytes()).unwrap();
+
+        let mut certificate = Certificate::new_for_test(network_id, 0);
+        assert_eq!(signer.address(), certificate.get_signer());
+        certificate.signature = Signature::new(Default::default(), Default::default(), false);
+        certificate.sign(&signer).unwrap();
+        assert_eq!(certificate.verify_signature(signer.address()), Ok(()));
+
+        let other = LocalSigner::random();
+        assert_eq!(
+            certificate.verify_signature(other.address()),
+            Err(Error::InvalidSigner {
+                expected: other.address(),
+                recovered: signer.address(),
+            })
+        );
+        assert!(matches!(
+            LocalSigner::from_bytes(&[0; 32]),
+            Err(SignerError::InvalidPrivateKey)
+        ));
+    }
+
+    #[test]
+    fn keystore_signer_roundtrip() {
+        let dir = std::env::temp_dir();
+        let name = format!("agglayer-keystore-{}", random::<u64>());
+        let created = KeystoreSigner::create(&dir, &name, "password").unwrap();
+
+        let opened = KeystoreSigner::open(dir.join(&name), "password").unwrap();
+        assert_eq!(opened.address(), created.address());
+        assert!(matches!(
+            KeystoreSigner::open(dir.join(&name), "wrong"),
+            Err(SignerError::Keystore(_))
+        ));
+
+        let mut certificate = Certificate::new_for_test(random::<u32>().into(), 0);
+        certificate.sign(&opened).unwrap();
+        assert_eq!(certificate.verify_signature(created.address()), Ok(()));
+
+        std::fs::remove_file(dir.join(&name)).unwrap();
+    }
+}
@@ -64,6 +64,65 @@ pub struct BridgeExit {
     pub metadata: Option<Digest>,
 }
 
+/// A bridge message, i.e., a call to a recipient contract on the destination
+/// network which carries an ETH value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeMessage {
+    /// Network which emitted the message
+    pub origin_network: NetworkId,
+    /// Address which sent the message on the origin network
+    pub origin_address: Address,
+    /// Network which the message is sent to
+    pub dest_network: NetworkId,
+    /// Contract which is called on the destination network
+    pub recipient: Address,
+    /// ETH value sent along with the message
+    pub value: U256,
+    /// Hash of the calldata passed to the recipient
+    pub calldata_hash: Digest,
+}
+
+impl BridgeMessage {
+    /// Hashes the [`BridgeMessage`] to be committed in the message exits.
+    pub fn hash(&self) -> Digest {
+        keccak256_combine([
+            &u32::to_be_bytes(self.origin_network),
+            self.origin_address.as_slice(),
+            &u32::to_be_bytes(self.dest_network),
+            self.recipient.as_slice(),
+            &self.value.to_be_bytes::<32>(),
+            self.calldata_hash.as_slice(),
+        ])
+    }
+}
+
+impl From<BridgeMessage> for BridgeExit {
+    fn from(message: BridgeMessage) -> Self {
+        Self {
+            leaf_type: LeafType::Message,
+            token_info: TokenInfo {
+                origin_network: message.origin_network,
+                origin_token_address: message.origin_address,
+            },
+            dest_network: message.dest_network,
+            dest_address: message.recipient,
+            amount: message.value,
+            metadata: Some(message.calldata_hash),
+        }
+    }
+}
+
+/// Commits to the message exits of one state transition, each along with the
+/// index of its leaf in the local exit tree.
+pub fn commit_message_exits(iter: impl Iterator<Item = (u32, BridgeMessage)>) -> Digest {
+    keccak256_combine(iter.map(|(leaf_index, message)| {
+        keccak256_combine([
+            leaf_index.to_be_bytes().as_slice(),
+            message.hash().as_slice(),
+        ])
+    }))
+}
+
 const EMPTY_METADATA_HASH: Digest = Digest(hex!(
     "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
 ));
@@ -107,6 +166,18 @@ impl BridgeExit {
         self.leaf_type == LeafType::Message
     }
 
+    /// Returns the [`BridgeMessage`] if the bridge exit is a message.
+    pub fn as_message(&self) -> Option<BridgeMessage> {
+        self.is_message().then(|| BridgeMessage {
+            origin_network: self.token_info.origin_network,
+            origin_address: self.token_info.origin_token_address,
+            dest_network: self.dest_network,
+            recipient: self.dest_address,
+            value: self.amount,
+            calldata_hash: self.metadata.unwrap_or(EMPTY_METADATA_HASH),
+        })
+    }
+
     /// Returns the [`TokenInfo`] considered for the the given amount.
     /// The amount corresponds to L1 ETH if the bridge exit is a message.
     pub fn amount_token_info(&self) -> TokenInfo {
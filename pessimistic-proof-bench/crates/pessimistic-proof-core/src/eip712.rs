@@ -0,0 +1,103 @@
+use agglayer_primitives::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bridge_exit::NetworkId,
+    keccak::{digest::Digest, keccak256, keccak256_combine},
+};
+
+/// Name of the EIP-712 signing domain.
+pub const EIP712_DOMAIN_NAME: &str = "AggLayer";
+
+/// Version of the EIP-712 signing domain.
+pub const EIP712_DOMAIN_VERSION: &str = "1";
+
+const EIP712_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+const CERTIFICATE_TYPE: &str = "Certificate(uint32 networkId,uint64 height,bytes32 \
+                                prevLocalExitRoot,bytes32 newLocalExitRoot,bytes32 \
+                                importedBridgeExitsHash,bytes32 metadata)";
+
+/// Chain and contract binding the EIP-712 signatures of the certificates, so
+/// that they cannot be replayed on another deployment of the agglayer.
+///
+/// The domain of each network is committed in its pessimistic root, and the
+/// proof rejects the signatures made for any other domain.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Eip712Domain {
+    /// Id of the chain on which the certificates are settled.
+    pub chain_id: u64,
+    /// Address of the contract verifying the certificates.
+    pub verifying_contract: Address,
+}
+
+/// Returns the EIP-712 domain separator of the agglayer certificates.
+pub fn domain_separator(domain: &Eip712Domain) -> Digest {
+    // Every field is ABI encoded on one left padded word
+    let mut chain_id_word = [0u8; 32];
+    chain_id_word[24..].copy_from_slice(&domain.chain_id.to_be_bytes());
+    let mut verifying_contract_word = [0u8; 32];
+    verifying_contract_word[12..].copy_from_slice(domain.verifying_contract.as_slice());
+
+    keccak256_combine([
+        keccak256(EIP712_DOMAIN_TYPE.as_bytes()).0,
+        keccak256(EIP712_DOMAIN_NAME.as_bytes()).0,
+        keccak256(EIP712_DOMAIN_VERSION.as_bytes()).0,
+        chain_id_word,
+        verifying_contract_word,
+    ])
+}
+
+/// Returns the EIP-712 struct hash of the certificate fields.
+pub fn certificate_struct_hash(
+    network_id: NetworkId,
+    height: u64,
+    prev_local_exit_root: Digest,
+    new_local_exit_root: Digest,
+    imported_bridge_exits_hash: Digest,
+    metadata: Digest,
+) -> Digest {
+    // Every field is ABI encoded on one left padded word
+    let mut network_id_word = [0u8; 32];
+    network_id_word[28..].copy_from_slice(&network_id.to_be_bytes());
+    let mut height_word = [0u8; 32];
+    height_word[24..].copy_from_slice(&height.to_be_bytes());
+
+    keccak256_combine([
+        keccak256(CERTIFICATE_TYPE.as_bytes()).0,
+        network_id_word,
+        height_word,
+        prev_local_exit_root.0,
+        new_local_exit_root.0,
+        imported_bridge_exits_hash.0,
+        metadata.0,
+    ])
+}
+
+/// Returns the EIP-712 digest signed for the given certificate fields, within
+/// the given domain.
+pub fn certificate_signing_hash(
+    domain: &Eip712Domain,
+    network_id: NetworkId,
+    height: u64,
+    prev_local_exit_root: Digest,
+    new_local_exit_root: Digest,
+    imported_bridge_exits_hash: Digest,
+    metadata: Digest,
+) -> Digest {
+    let struct_hash = certificate_struct_hash(
+        network_id,
+        height,
+        prev_local_exit_root,
+        new_local_exit_root,
+        imported_bridge_exits_hash,
+        metadata,
+    );
+
+    keccak256_combine([
+        [0x19, 0x01].as_slice(),
+        domain_separator(domain).as_slice(),
+        struct_hash.as_slice(),
+    ])
+}
@@ -0,0 +1,7 @@
+use crate::keccak::{digest::Digest, keccak256_combine};
+
+/// Commits to the list of forced withdrawals pending on a frozen network, given
+/// as the hashes of the bridge exits allowed to leave the network.
+pub fn commit_forced_withdrawals(iter: impl Iterator<Item = Digest>) -> Digest {
+    keccak256_combine(iter)
+}
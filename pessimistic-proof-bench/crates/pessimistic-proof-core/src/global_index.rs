@@ -1,5 +1,6 @@
 use agglayer_primitives::U256;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
     bridge_exit::NetworkId,
@@ -20,9 +21,26 @@ pub struct GlobalIndex {
     pub leaf_index: u32,
 }
 
+/// Errors raised when decoding a [`GlobalIndex`] from its [`U256`] encoding.
+#[derive(Clone, Debug, Error, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GlobalIndexError {
+    /// Some bits reserved to zero by the encoding are set.
+    #[error("The global index has reserved bits set.")]
+    ReservedBitsSet,
+    /// The global index refers to mainnet but has a non-zero rollup index.
+    #[error("The mainnet global index has a non-zero rollup index: {rollup_index}.")]
+    NonZeroMainnetRollupIndex { rollup_index: u32 },
+}
+
 impl GlobalIndex {
     const MAINNET_FLAG_OFFSET: usize = 2 * 32;
 
+    /// Returns whether this global index has a unique [`U256`] encoding, i.e.,
+    /// whether the rollup index is zero for mainnet.
+    pub fn is_canonical(&self) -> bool {
+        !self.mainnet_flag || self.rollup_index == 0
+    }
+
     pub fn network_id(&self) -> NetworkId {
         if self.mainnet_flag {
             0
@@ -46,8 +64,15 @@ impl From<GlobalIndex> for NullifierKey {
     }
 }
 
-impl From<U256> for GlobalIndex {
-    fn from(value: U256) -> Self {
+impl TryFrom<U256> for GlobalIndex {
+    type Error = GlobalIndexError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        // Only the leaf index, the rollup index and the mainnet flag can be set.
+        if value >> (Self::MAINNET_FLAG_OFFSET + 1) != U256::ZERO {
+            return Err(GlobalIndexError::ReservedBitsSet);
+        }
+
         let bytes = value.as_le_slice();
 
         let mainnet_flag = value.bit(Self::MAINNET_FLAG_OFFSET);
@@ -58,11 +83,17 @@ impl From<U256> for GlobalIndex {
         let rollup_index = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
         let leaf_index = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
 
-        Self {
+        let global_index = Self {
             mainnet_flag,
             rollup_index,
             leaf_index,
+        };
+
+        if !global_index.is_canonical() {
+            return Err(GlobalIndexError::NonZeroMainnetRollupIndex { rollup_index });
         }
+
+        Ok(global_index)
     }
 }
 
@@ -86,15 +117,26 @@ impl From<GlobalIndex> for U256 {
 
 #[cfg(test)]
 mod tests {
+    use rand::random;
+
     use super::*;
 
     fn check(raw: &str, expected: GlobalIndex) {
         let global_index_u256 = U256::from_str_radix(raw, 10).unwrap();
         assert_eq!(
             global_index_u256,
-            GlobalIndex::from(global_index_u256).into()
+            GlobalIndex::try_from(global_index_u256).unwrap().into()
         );
-        assert_eq!(expected, GlobalIndex::from(global_index_u256));
+        assert_eq!(Ok(expected), GlobalIndex::try_from(global_index_u256));
+    }
+
+    fn random_canonical() -> GlobalIndex {
+        let mainnet_flag = random();
+        GlobalIndex {
+            mainnet_flag,
+            rollup_index: if mainnet_flag { 0 } else { random() },
+            leaf_index: random(),
+        }
     }
 
     #[test]
@@ -119,4 +161,44 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn canonical_round_trip() {
+        for _ in 0..1000 {
+            let global_index = random_canonical();
+            let encoded: U256 = global_index.into();
+            assert_eq!(GlobalIndex::try_from(encoded), Ok(global_index));
+        }
+    }
+
+    #[test]
+    fn reject_reserved_bits() {
+        for _ in 0..1000 {
+            let encoded: U256 = random_canonical().into();
+            let reserved_bit = GlobalIndex::MAINNET_FLAG_OFFSET + 1 + random::<usize>() % 191;
+            let tampered = encoded | (U256::from(1u8) << reserved_bit);
+            assert_eq!(
+                GlobalIndex::try_from(tampered),
+                Err(GlobalIndexError::ReservedBitsSet)
+            );
+        }
+    }
+
+    #[test]
+    fn reject_mainnet_with_rollup_index() {
+        for _ in 0..1000 {
+            let rollup_index = random::<u32>().max(1);
+            let global_index = GlobalIndex {
+                mainnet_flag: true,
+                rollup_index,
+                leaf_index: random(),
+            };
+            assert!(!global_index.is_canonical());
+            let encoded: U256 = global_index.into();
+            assert_eq!(
+                GlobalIndex::try_from(encoded),
+                Err(GlobalIndexError::NonZeroMainnetRollupIndex { rollup_index })
+            );
+        }
+    }
 }
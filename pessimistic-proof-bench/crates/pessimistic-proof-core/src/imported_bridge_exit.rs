@@ -37,6 +37,44 @@ impl L1InfoTreeLeaf {
     pub fn hash(&self) -> Digest {
         self.inner.hash()
     }
+
+    /// Verifies that the leaf is within the first `leaf_count` leaves of the L1
+    /// info tree, and that it is not older than the given minimum timestamp.
+    pub fn verify_bounds(&self, leaf_count: u32, min_timestamp: Option<u64>) -> Result<(), Error> {
+        if self.l1_info_tree_index >= leaf_count {
+            return Err(Error::L1InfoTreeIndexOutOfBounds {
+                index: self.l1_info_tree_index,
+                leaf_count,
+            });
+        }
+
+        if let Some(min_timestamp) = min_timestamp {
+            if self.inner.timestamp < min_timestamp {
+                return Err(Error::StaleL1InfoTreeLeaf {
+                    timestamp: self.inner.timestamp,
+                    min_timestamp,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the global exit root of the leaf commits to the declared
+    /// MER and RER.
+    pub fn verify_global_exit_root(&self) -> Result<(), Error> {
+        if self.inner.global_exit_root != compute_global_exit_root(self.mer, self.rer) {
+            return Err(Error::MismatchGlobalExitRoot);
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the global exit root from the mainnet exit root and the rollup
+/// exit root, as done by the L1 global exit root manager.
+pub fn compute_global_exit_root(mainnet_exit_root: Digest, rollup_exit_root: Digest) -> Digest {
+    keccak256_combine([mainnet_exit_root.as_slice(), rollup_exit_root.as_slice()])
 }
 
 #[derive(Clone, Debug, Error, Serialize, Deserialize, PartialEq, Eq)]
@@ -45,6 +83,9 @@ pub enum Error {
     /// same network type: mainnet or rollup.
     #[error("Mismatch between the global index and the inclusion proof.")]
     MismatchGlobalIndexInclusionProof,
+    /// The global index refers to mainnet but has a non-zero rollup index.
+    #[error("The global index is not canonical: mainnet with a non-zero rollup index.")]
+    NonCanonicalGlobalIndex,
     /// The provided L1 info root does not match the one provided in the
     /// inclusion proof.
     #[error("Mismatch between the provided L1 root and the inclusion proof.")]
@@ -57,6 +98,17 @@ pub enum Error {
     /// inclusion proof.
     #[error("Mismatch on the RER between the L1 leaf and the inclusion proof.")]
     MismatchRER,
+    /// The global exit root of the provided L1 info leaf does not commit to
+    /// its declared MER and RER.
+    #[error("Mismatch between the GER of the L1 leaf and its MER and RER.")]
+    MismatchGlobalExitRoot,
+    /// The L1 info tree leaf is beyond the number of leaves considered for the
+    /// L1 info root.
+    #[error("The L1 info tree index {index} is out of bounds, leaf count: {leaf_count}.")]
+    L1InfoTreeIndexOutOfBounds { index: u32, leaf_count: u32 },
+    /// The L1 info tree leaf is older than the minimum timestamp.
+    #[error("The L1 info tree leaf is stale. timestamp: {timestamp}, minimum: {min_timestamp}.")]
+    StaleL1InfoTreeLeaf { timestamp: u64, min_timestamp: u64 },
     /// The inclusion proof from the leaf to the LER is invalid.
     #[error("Invalid merkle path from the leaf to the LER.")]
     InvalidMerklePathLeafToLER,
@@ -117,6 +169,9 @@ impl ClaimFromMainnet {
             return Err(Error::MismatchMER);
         }
 
+        // Check that the declared MER and RER are committed in the GER
+        self.l1_leaf.verify_global_exit_root()?;
+
         // Check the inclusion proof of the leaf to the LER (here LER is the MER)
         if !self.proof_leaf_mer.verify(leaf, global_index.leaf_index) {
             return Err(Error::InvalidMerklePathLeafToLER);
@@ -163,6 +218,9 @@ impl ClaimFromRollup {
             return Err(Error::MismatchRER);
         }
 
+        // Check that the declared MER and RER are committed in the GER
+        self.l1_leaf.verify_global_exit_root()?;
+
         // Check the inclusion proof of the leaf to the LER
         if !self.proof_leaf_ler.verify(leaf, global_index.leaf_index) {
             return Err(Error::InvalidMerklePathLeafToLER);
@@ -204,6 +262,14 @@ pub struct ImportedBridgeExit {
 }
 
 impl ImportedBridgeExit {
+    /// Returns the L1 info tree leaf against which the claim is done.
+    pub fn l1_leaf(&self) -> &L1InfoTreeLeaf {
+        match &self.claim_data {
+            Claim::Mainnet(claim) => &claim.l1_leaf,
+            Claim::Rollup(claim) => &claim.l1_leaf,
+        }
+    }
+
     /// Verifies that the provided inclusion path is valid and consistent with
     /// the provided LER
     pub fn verify_path(&self, l1root: Digest) -> Result<(), Error> {
@@ -213,6 +279,12 @@ impl ImportedBridgeExit {
             return Err(Error::MismatchGlobalIndexInclusionProof);
         }
 
+        // Check that the global index has a unique encoding, otherwise two
+        // different global indices would map onto the same nullifier key
+        if !self.global_index.is_canonical() {
+            return Err(Error::NonCanonicalGlobalIndex);
+        }
+
         match &self.claim_data {
             Claim::Mainnet(claim) => {
                 claim.verify(self.bridge_exit.hash(), self.global_index, l1root)
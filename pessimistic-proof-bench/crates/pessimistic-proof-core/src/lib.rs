@@ -2,17 +2,27 @@ pub mod keccak;
 pub mod local_exit_tree;
 
 pub mod proof;
-pub use proof::{generate_pessimistic_proof, PessimisticProofOutput, ProofError};
+pub use proof::{
+    generate_pessimistic_proof, generate_pessimistic_proof_with_profiler, PessimisticProofOutput,
+    ProofError,
+};
 
 pub mod local_balance_tree;
 
 pub mod bridge_exit;
+pub mod eip712;
+pub mod forced_withdrawals;
 
 pub mod global_index;
 pub mod imported_bridge_exit;
 pub mod local_state;
 pub mod multi_batch_header;
+pub mod native_supply_tree;
+pub mod network_config;
 pub mod nullifier_tree;
+pub mod outflow_limits_tree;
+pub mod profiling;
+pub mod signer_set;
 pub mod utils;
 
 pub use local_state::NetworkState;
@@ -4,16 +4,41 @@ use agglayer_primitives::{ruint::UintTryFrom, B256, U256, U512};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    bridge_exit::{L1_ETH, L1_NETWORK_ID},
+    bridge_exit::{TokenInfo, L1_ETH, L1_NETWORK_ID},
+    eip712::{domain_separator, Eip712Domain},
+    forced_withdrawals::commit_forced_withdrawals,
     imported_bridge_exit::{commit_imported_bridge_exits, Error},
-    keccak::digest::Digest,
+    keccak::{digest::Digest, keccak256_combine},
     local_balance_tree::LocalBalanceTree,
     local_exit_tree::{hasher::Keccak256Hasher, LocalExitTree},
-    multi_batch_header::{signature_commitment, MultiBatchHeader},
+    multi_batch_header::{CommitmentMode, MultiBatchHeader},
+    native_supply_tree::{NativeSupply, NativeSupplyTree},
     nullifier_tree::{NullifierKey, NullifierTree},
+    outflow_limits_tree::OutflowLimitsTree,
+    profiling::{NoopProfiler, Phase, Profiler},
+    signer_set::{apply_signer_rotation, commit_signer_set},
     ProofError,
 };
 
+/// Domain separation tag of the outflow limits root within the pessimistic
+/// root.
+const OUTFLOW_LIMITS_ROOT_TAG: u8 = 0x01;
+
+/// Domain separation tag of the native supply root within the pessimistic
+/// root.
+const NATIVE_SUPPLY_ROOT_TAG: u8 = 0x02;
+
+/// Domain separation tag of the forced withdrawals root within the pessimistic
+/// root.
+const FORCED_WITHDRAWALS_ROOT_TAG: u8 = 0x03;
+
+/// Domain separation tag of the signer set root within the pessimistic root.
+const SIGNER_SET_ROOT_TAG: u8 = 0x04;
+
+/// Domain separation tag of the EIP-712 domain separator within the
+/// pessimistic root.
+const EIP712_DOMAIN_TAG: u8 = 0x05;
+
 /// State representation of one network without the leaves, taken as input by
 /// the prover.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -25,6 +50,21 @@ pub struct NetworkState {
     /// Commitment to the Nullifier tree for the local network, tracks claimed
     /// assets on foreign networks
     pub nullifier_tree: NullifierTree<Keccak256Hasher>,
+    /// Commitment to the per-certificate outflow limit of each token. None if
+    /// the outflows are not capped.
+    pub outflow_limits_tree: Option<OutflowLimitsTree<Keccak256Hasher>>,
+    /// Commitment to the cumulative exported and imported amounts of each
+    /// native token. None if the native supply is not tracked.
+    pub native_supply_tree: Option<NativeSupplyTree<Keccak256Hasher>>,
+    /// Commitment to the forced withdrawals pending on the network while it is
+    /// frozen. None if the network is not frozen.
+    pub forced_withdrawals_root: Option<Digest>,
+    /// Commitment to the set of signers authorized to sign the state
+    /// transitions. None if any signer is accepted.
+    pub signer_set_root: Option<Digest>,
+    /// Domain of the EIP-712 signatures of the state transitions. None if the
+    /// legacy commitment is signed.
+    pub eip712_domain: Option<Eip712Domain>,
 }
 
 /// The roots of one [`LocalNetworkState`].
@@ -34,6 +74,66 @@ pub struct StateCommitment {
     pub ler_leaf_count: u32,
     pub balance_root: Digest,
     pub nullifier_root: Digest,
+    pub outflow_limits_root: Option<Digest>,
+    pub native_supply_root: Option<Digest>,
+    pub forced_withdrawals_root: Option<Digest>,
+    pub signer_set_root: Option<Digest>,
+    pub eip712_domain_separator: Option<Digest>,
+}
+
+impl StateCommitment {
+    /// Returns the pessimistic root, which commits to the balance and nullifier
+    /// trees, to the number of leaves in the local exit tree, and to the
+    /// optional extensions of the state.
+    pub fn pessimistic_root(&self) -> Digest {
+        let mut root = keccak256_combine([
+            self.balance_root.as_slice(),
+            self.nullifier_root.as_slice(),
+            self.ler_leaf_count.to_le_bytes().as_slice(),
+        ]);
+
+        if let Some(outflow_limits_root) = self.outflow_limits_root {
+            root = keccak256_combine([
+                root.as_slice(),
+                [OUTFLOW_LIMITS_ROOT_TAG].as_slice(),
+                outflow_limits_root.as_slice(),
+            ]);
+        }
+
+        if let Some(native_supply_root) = self.native_supply_root {
+            root = keccak256_combine([
+                root.as_slice(),
+                [NATIVE_SUPPLY_ROOT_TAG].as_slice(),
+                native_supply_root.as_slice(),
+            ]);
+        }
+
+        if let Some(forced_withdrawals_root) = self.forced_withdrawals_root {
+            root = keccak256_combine([
+                root.as_slice(),
+                [FORCED_WITHDRAWALS_ROOT_TAG].as_slice(),
+                forced_withdrawals_root.as_slice(),
+            ]);
+        }
+
+        if let Some(signer_set_root) = self.signer_set_root {
+            root = keccak256_combine([
+                root.as_slice(),
+                [SIGNER_SET_ROOT_TAG].as_slice(),
+                signer_set_root.as_slice(),
+            ]);
+        }
+
+        if let Some(eip712_domain_separator) = self.eip712_domain_separator {
+            root = keccak256_combine([
+                root.as_slice(),
+                [EIP712_DOMAIN_TAG].as_slice(),
+                eip712_domain_separator.as_slice(),
+            ]);
+        }
+
+        root
+    }
 }
 
 impl NetworkState {
@@ -44,6 +144,11 @@ impl NetworkState {
             ler_leaf_count: self.exit_tree.leaf_count,
             balance_root: self.balance_tree.root,
             nullifier_root: self.nullifier_tree.root,
+            outflow_limits_root: self.outflow_limits_tree.as_ref().map(|tree| tree.root),
+            native_supply_root: self.native_supply_tree.as_ref().map(|tree| tree.root),
+            forced_withdrawals_root: self.forced_withdrawals_root,
+            signer_set_root: self.signer_set_root,
+            eip712_domain_separator: self.eip712_domain.as_ref().map(domain_separator),
         }
     }
 
@@ -53,9 +158,19 @@ impl NetworkState {
     pub fn apply_batch_header(
         &mut self,
         multi_batch_header: &MultiBatchHeader<Keccak256Hasher>,
+    ) -> Result<StateCommitment, ProofError> {
+        self.apply_batch_header_with_profiler(multi_batch_header, &mut NoopProfiler)
+    }
+
+    /// Same as [`Self::apply_batch_header`], reporting its phases to the given
+    /// [`Profiler`].
+    pub fn apply_batch_header_with_profiler(
+        &mut self,
+        multi_batch_header: &MultiBatchHeader<Keccak256Hasher>,
+        profiler: &mut impl Profiler,
     ) -> Result<StateCommitment, ProofError> {
         let mut clone = self.clone();
-        let roots = clone.apply_batch_header_helper(multi_batch_header)?;
+        let roots = clone.apply_batch_header_helper(multi_batch_header, profiler)?;
         *self = clone;
 
         Ok(roots)
@@ -67,27 +182,37 @@ impl NetworkState {
     fn apply_batch_header_helper(
         &mut self,
         multi_batch_header: &MultiBatchHeader<Keccak256Hasher>,
+        profiler: &mut impl Profiler,
     ) -> Result<StateCommitment, ProofError> {
         // Check the initial state
-        let computed_root = self.exit_tree.get_root();
-        if computed_root != multi_batch_header.prev_local_exit_root {
-            return Err(ProofError::InvalidPreviousLocalExitRoot {
-                computed: computed_root,
-                declared: multi_batch_header.prev_local_exit_root,
-            });
-        }
-        if self.balance_tree.root != multi_batch_header.prev_balance_root {
-            return Err(ProofError::InvalidPreviousBalanceRoot {
-                computed: self.balance_tree.root,
-                declared: multi_batch_header.prev_balance_root,
-            });
-        }
+        profiler.measure(Phase::PrevRootChecks, || {
+            let computed_root = self.exit_tree.get_root();
+            if computed_root != multi_batch_header.prev_local_exit_root {
+                return Err(ProofError::InvalidPreviousLocalExitRoot {
+                    computed: computed_root,
+                    declared: multi_batch_header.prev_local_exit_root,
+                });
+            }
+            if self.balance_tree.root != multi_batch_header.prev_balance_root {
+                return Err(ProofError::InvalidPreviousBalanceRoot {
+                    computed: self.balance_tree.root,
+                    declared: multi_batch_header.prev_balance_root,
+                });
+            }
 
-        if self.nullifier_tree.root != multi_batch_header.prev_nullifier_root {
-            return Err(ProofError::InvalidPreviousNullifierRoot {
-                computed: self.nullifier_tree.root,
-                declared: multi_batch_header.prev_nullifier_root,
-            });
+            if self.nullifier_tree.root != multi_batch_header.prev_nullifier_root {
+                return Err(ProofError::InvalidPreviousNullifierRoot {
+                    computed: self.nullifier_tree.root,
+                    declared: multi_batch_header.prev_nullifier_root,
+                });
+            }
+
+            Ok(())
+        })?;
+
+        // Apply the configuration set by the agglayer since the previous transition
+        if let Some(config_update) = &multi_batch_header.config_update {
+            config_update.apply(self);
         }
 
         // TODO: benchmark if BTreeMap is the best choice in terms of SP1 cycles
@@ -117,6 +242,9 @@ impl NetworkState {
             return Err(ProofError::MismatchImportedExitsRoot);
         }
 
+        // Cumulated amounts of the native tokens bridged in and out
+        let mut native_flows: BTreeMap<TokenInfo, NativeSupply> = BTreeMap::new();
+
         // Apply the imported bridge exits
         for (imported_bridge_exit, nullifier_path) in &multi_batch_header.imported_bridge_exits {
             if imported_bridge_exit.global_index.network_id() == multi_batch_header.origin_network {
@@ -132,9 +260,23 @@ impl NetworkState {
                 });
             }
 
-            // Check the inclusion proof
+            // Check that the L1 info leaf is within the committed bounds
             imported_bridge_exit
-                .verify_path(multi_batch_header.l1_info_root)
+                .l1_leaf()
+                .verify_bounds(
+                    multi_batch_header.l1_info_tree_leaf_count,
+                    multi_batch_header.min_l1_info_timestamp,
+                )
+                .map_err(|source| ProofError::InvalidImportedBridgeExit {
+                    source,
+                    global_index: imported_bridge_exit.global_index,
+                })?;
+
+            // Check the inclusion proof
+            profiler
+                .measure(Phase::ImportedExitPaths, || {
+                    imported_bridge_exit.verify_path(multi_batch_header.l1_info_root)
+                })
                 .map_err(|source| ProofError::InvalidImportedBridgeExit {
                     source,
                     global_index: imported_bridge_exit.global_index,
@@ -142,14 +284,22 @@ impl NetworkState {
 
             // Check the nullifier non-inclusion path and update the nullifier tree
             let nullifier_key: NullifierKey = imported_bridge_exit.global_index.into();
-            self.nullifier_tree
-                .verify_and_update(nullifier_key, nullifier_path)?;
+            profiler.measure(Phase::NullifierUpdates, || {
+                self.nullifier_tree
+                    .verify_and_update(nullifier_key, nullifier_path)
+            })?;
 
             // The amount corresponds to L1 ETH if the leaf is a message
             let token_info = imported_bridge_exit.bridge_exit.amount_token_info();
 
             if multi_batch_header.origin_network == token_info.origin_network {
-                // When the token is native to the chain, we don't care about the local balance
+                // When the token is native to the chain, we don't care about the local balance,
+                // but we record the native supply coming back
+                let flow = native_flows.entry(token_info).or_default();
+                flow.imported = flow
+                    .imported
+                    .checked_add(imported_bridge_exit.bridge_exit.amount)
+                    .ok_or(ProofError::NativeSupplyOverflow(token_info))?;
                 continue;
             }
 
@@ -167,13 +317,39 @@ impl NetworkState {
             }
         }
 
+        // Check the forced withdrawals pending on the frozen network
+        let mut forced_withdrawals = match self.forced_withdrawals_root {
+            None => None,
+            Some(declared) => {
+                let computed = commit_forced_withdrawals(
+                    multi_batch_header.forced_withdrawals.iter().copied(),
+                );
+                if computed != declared {
+                    return Err(ProofError::InvalidForcedWithdrawals { declared, computed });
+                }
+                Some(multi_batch_header.forced_withdrawals.clone())
+            }
+        };
+
         // Apply the bridge exits
+        let mut outflows: BTreeMap<TokenInfo, U256> = BTreeMap::new();
         for bridge_exit in &multi_batch_header.bridge_exits {
             if bridge_exit.dest_network == multi_batch_header.origin_network {
                 // We don't allow a chain to exit to itself
                 return Err(ProofError::CannotExitToSameNetwork);
             }
-            self.exit_tree.add_leaf(bridge_exit.hash())?;
+            let bridge_exit_hash = bridge_exit.hash();
+
+            // While frozen, only the forced withdrawals can leave the network, once each
+            if let Some(forced_withdrawals) = &mut forced_withdrawals {
+                let index = forced_withdrawals
+                    .iter()
+                    .position(|hash| *hash == bridge_exit_hash)
+                    .ok_or(ProofError::BridgeExitWhileFrozen(bridge_exit_hash))?;
+                forced_withdrawals.remove(index);
+            }
+
+            self.exit_tree.add_leaf(bridge_exit_hash)?;
 
             // For message exits, the origin network in token info should be the origin
             // network of the batch header.
@@ -193,8 +369,22 @@ impl NetworkState {
             // The amount corresponds to L1 ETH if the leaf is a message
             let token_info = bridge_exit.amount_token_info();
 
+            // Track the outflow when capped, native tokens included
+            if self.outflow_limits_tree.is_some() {
+                let outflow = outflows.entry(token_info).or_default();
+                *outflow = outflow
+                    .checked_add(bridge_exit.amount)
+                    .ok_or(ProofError::OutflowOverflow(token_info))?;
+            }
+
             if multi_batch_header.origin_network == token_info.origin_network {
-                // When the token is native to the chain, we don't care about the local balance
+                // When the token is native to the chain, we don't care about the local balance,
+                // but we record the native supply going out
+                let flow = native_flows.entry(token_info).or_default();
+                flow.exported = flow
+                    .exported
+                    .checked_add(bridge_exit.amount)
+                    .ok_or(ProofError::NativeSupplyOverflow(token_info))?;
                 continue;
             }
 
@@ -212,6 +402,38 @@ impl NetworkState {
             }
         }
 
+        // Commit to the remaining forced withdrawals
+        if let Some(forced_withdrawals) = forced_withdrawals {
+            self.forced_withdrawals_root =
+                Some(commit_forced_withdrawals(forced_withdrawals.into_iter()));
+        }
+
+        // Verify that the outflow of each token is within its limit
+        if let Some(outflow_limits_tree) = &self.outflow_limits_tree {
+            for (token, outflow) in outflows {
+                let (limit, path) = multi_batch_header
+                    .outflow_limits_proofs
+                    .get(&token)
+                    .ok_or(ProofError::MissingOutflowLimitProof(token))?;
+                outflow_limits_tree.verify_outflow(token, path, *limit, outflow)?;
+            }
+        }
+
+        // Verify that the original native supplies were correct and update the native
+        // supply tree with the new supplies
+        if let Some(native_supply_tree) = &mut self.native_supply_tree {
+            for (token, flow) in native_flows {
+                let (old_supply, path) = multi_batch_header
+                    .native_supply_proofs
+                    .get(&token)
+                    .ok_or(ProofError::MissingNativeSupplyProof(token))?;
+                let new_supply = old_supply
+                    .checked_add(&flow)
+                    .ok_or(ProofError::NativeSupplyOverflow(token))?;
+                native_supply_tree.verify_and_update(token, path, old_supply, &new_supply)?;
+            }
+        }
+
         // Verify that the original balances were correct and update the local balance
         // tree with the new balances. TODO: implement batch `verify_and_update`
         // for the LBT
@@ -219,24 +441,43 @@ impl NetworkState {
             let new_balance = new_balances[token];
             let new_balance = U256::uint_try_from(new_balance)
                 .map_err(|_| ProofError::BalanceOverflowInBridgeExit)?;
-            self.balance_tree
-                .verify_and_update(*token, balance_path, *old_balance, new_balance)?;
+            profiler.measure(Phase::BalanceUpdates, || {
+                self.balance_tree
+                    .verify_and_update(*token, balance_path, *old_balance, new_balance)
+            })?;
         }
 
-        // Verify that the signature is valid
-        let combined_hash = signature_commitment(
-            self.exit_tree.get_root(),
-            multi_batch_header
-                .imported_bridge_exits
-                .iter()
-                .map(|(exit, _)| exit.global_index),
-        );
+        // The commitment signed is the one configured for the network
+        let expected_mode = self
+            .eip712_domain
+            .map_or(CommitmentMode::Legacy, CommitmentMode::Eip712);
+        if multi_batch_header.commitment_mode != expected_mode {
+            return Err(ProofError::InvalidCommitmentMode {
+                declared: multi_batch_header.commitment_mode,
+                expected: expected_mode,
+            });
+        }
+
+        let signer = profiler.measure(Phase::SignatureRecovery, || {
+            // Verify that the signature is valid
+            let combined_hash = multi_batch_header.commitment_mode.commitment(
+                multi_batch_header.origin_network,
+                multi_batch_header.height,
+                multi_batch_header.prev_local_exit_root,
+                self.exit_tree.get_root(),
+                multi_batch_header
+                    .imported_bridge_exits
+                    .iter()
+                    .map(|(exit, _)| exit.global_index),
+                multi_batch_header.metadata,
+            );
 
-        // Check batch header signature
-        let signer = multi_batch_header
-            .signature
-            .recover_address_from_prehash(&B256::new(combined_hash.0))
-            .map_err(|_| ProofError::InvalidSignature)?;
+            // Check batch header signature
+            multi_batch_header
+                .signature
+                .recover_address_from_prehash(&B256::new(combined_hash.0))
+                .map_err(|_| ProofError::InvalidSignature)
+        })?;
 
         if signer != multi_batch_header.signer {
             return Err(ProofError::InvalidSigner {
@@ -245,6 +486,32 @@ impl NetworkState {
             });
         }
 
+        // Check that the signer is authorized, after the optional signer rotation
+        match self.signer_set_root {
+            Some(declared) => {
+                let computed = commit_signer_set(
+                    multi_batch_header.signer_set_nonce,
+                    multi_batch_header.authorized_signers.iter().copied(),
+                );
+                if computed != declared {
+                    return Err(ProofError::InvalidSignerSet { declared, computed });
+                }
+
+                let (nonce, signers) = apply_signer_rotation(
+                    multi_batch_header.origin_network,
+                    multi_batch_header.signer_set_nonce,
+                    multi_batch_header.authorized_signers.clone(),
+                    multi_batch_header.signer_rotation.as_ref(),
+                    multi_batch_header.signer,
+                )?;
+                self.signer_set_root = Some(commit_signer_set(nonce, signers.into_iter()));
+            }
+            None if multi_batch_header.signer_rotation.is_some() => {
+                return Err(ProofError::UnexpectedSignerRotation);
+            }
+            None => {}
+        }
+
         Ok(self.roots())
     }
 }
@@ -7,13 +7,18 @@ use serde_with::serde_as;
 
 use crate::{
     bridge_exit::{BridgeExit, NetworkId, TokenInfo},
+    eip712::{certificate_signing_hash, Eip712Domain},
     global_index::GlobalIndex,
     imported_bridge_exit::{commit_imported_bridge_exits, ImportedBridgeExit},
     keccak::{digest::Digest, keccak256_combine},
     local_balance_tree::LocalBalancePath,
     local_exit_tree::hasher::Hasher,
     local_state::StateCommitment,
+    native_supply_tree::{NativeSupply, NativeSupplyPath},
+    network_config::NetworkConfig,
     nullifier_tree::NullifierPath,
+    outflow_limits_tree::OutflowLimitPath,
+    signer_set::SignerRotation,
 };
 
 /// Represents the chain state transition for the pessimistic proof.
@@ -26,6 +31,8 @@ where
 {
     /// Network that emitted this [`MultiBatchHeader`].
     pub origin_network: NetworkId,
+    /// Height of the certificate on the origin network.
+    pub height: u64,
     /// Previous local exit root.
     #[serde_as(as = "_")]
     pub prev_local_exit_root: H::Digest,
@@ -46,13 +53,53 @@ where
     /// L1 info root used to import bridge exits.
     #[serde_as(as = "_")]
     pub l1_info_root: H::Digest,
+    /// Number of leaves in the L1 info tree at the time of `l1_info_root`.
+    /// Imported bridge exits cannot refer to leaves beyond this count.
+    pub l1_info_tree_leaf_count: u32,
+    /// Minimum timestamp of the L1 info tree leaves referred to by the imported
+    /// bridge exits. None if unconstrained.
+    pub min_l1_info_timestamp: Option<u64>,
+    /// Configuration set by the agglayer since the previous state transition,
+    /// applied before processing bridge events. None if unchanged.
+    pub config_update: Option<NetworkConfig>,
     /// Token balances of the origin network before processing bridge events,
     /// with Merkle proofs of these balances in the local balance tree.
+    #[serde_as(as = "Vec<(_, _)>")]
     pub balances_proofs: BTreeMap<TokenInfo, (U256, LocalBalancePath<H>)>,
+    /// Outflow limits of the tokens leaving the origin network, with Merkle
+    /// proofs of these limits in the outflow limits tree, after the optional
+    /// configuration update. Empty if the outflows
+    /// are not capped.
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub outflow_limits_proofs: BTreeMap<TokenInfo, (U256, OutflowLimitPath<H>)>,
+    /// Native supply of the native tokens bridged in or out before processing
+    /// bridge events, with Merkle proofs of these supplies in the native supply
+    /// tree. Empty if the native supply is not tracked.
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub native_supply_proofs: BTreeMap<TokenInfo, (NativeSupply, NativeSupplyPath<H>)>,
+    /// Forced withdrawals pending on the origin network before processing
+    /// bridge events and after the optional configuration update, as bridge
+    /// exit hashes. Empty if the network is not
+    /// frozen.
+    pub forced_withdrawals: Vec<Digest>,
     /// Signer committing to the state transition.
     pub signer: Address,
     /// Signature committing to the state transition.
     pub signature: Signature,
+    /// Scheme of the commitment signed by the signer.
+    pub commitment_mode: CommitmentMode,
+    /// Arbitrary data of the origin network, committed by the EIP-712
+    /// signature.
+    pub metadata: Digest,
+    /// Signers authorized on the origin network after the optional
+    /// configuration update, and before processing the optional signer
+    /// rotation. Empty if no signer set is committed.
+    pub authorized_signers: Vec<Address>,
+    /// Nonce of the authorized signer set, counting its changes so far.
+    pub signer_set_nonce: u64,
+    /// Replacement of one authorized signer, applied before checking the
+    /// signer.
+    pub signer_rotation: Option<SignerRotation>,
     /// State commitment target hashes.
     pub target: StateCommitment,
 }
@@ -64,3 +111,43 @@ pub fn signature_commitment(
     let imported_hash = commit_imported_bridge_exits(imported_bridge_exits);
     keccak256_combine([new_local_exit_root.as_slice(), imported_hash.as_slice()])
 }
+
+/// Scheme of the commitment signed for a state transition.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CommitmentMode {
+    /// Raw keccak of the new local exit root and of the imported bridge exits.
+    #[default]
+    Legacy,
+    /// EIP-712 typed data covering the network id, the height, the previous
+    /// and new local exit roots, the imported bridge exits and the metadata,
+    /// within the given domain.
+    Eip712(Eip712Domain),
+}
+
+impl CommitmentMode {
+    /// Returns the commitment to sign for the given certificate fields.
+    pub fn commitment(
+        &self,
+        network_id: NetworkId,
+        height: u64,
+        prev_local_exit_root: Digest,
+        new_local_exit_root: Digest,
+        imported_bridge_exits: impl Iterator<Item = GlobalIndex>,
+        metadata: Digest,
+    ) -> Digest {
+        match self {
+            CommitmentMode::Legacy => {
+                signature_commitment(new_local_exit_root, imported_bridge_exits)
+            }
+            CommitmentMode::Eip712(domain) => certificate_signing_hash(
+                domain,
+                network_id,
+                height,
+                prev_local_exit_root,
+                new_local_exit_root,
+                commit_imported_bridge_exits(imported_bridge_exits),
+                metadata,
+            ),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use agglayer_primitives::U256;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::{
+    bridge_exit::TokenInfo,
+    keccak::{digest::Digest, keccak256_combine},
+    local_exit_tree::hasher::{Hasher, Keccak256Hasher},
+    utils::smt::SmtMerkleProof,
+    ProofError,
+};
+
+/// The key is [`TokenInfo`] which can be packed into 192 bits (32 for network
+/// id and 160 for token address).
+pub const NATIVE_SUPPLY_TREE_DEPTH: usize = 192;
+
+/// Cumulative amounts of one native token which left and came back to the
+/// local network.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NativeSupply {
+    /// Total amount sent out through bridge exits.
+    pub exported: U256,
+    /// Total amount claimed back through imported bridge exits.
+    pub imported: U256,
+}
+
+impl NativeSupply {
+    /// Returns the leaf committing to this supply. The empty supply maps onto
+    /// the empty leaf.
+    pub fn hash(&self) -> Digest {
+        if *self == Self::default() {
+            return Digest::default();
+        }
+
+        keccak256_combine([
+            self.exported.to_be_bytes::<32>(),
+            self.imported.to_be_bytes::<32>(),
+        ])
+    }
+
+    /// Adds the given amounts, returns `None` on overflow.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(Self {
+            exported: self.exported.checked_add(other.exported)?,
+            imported: self.imported.checked_add(other.imported)?,
+        })
+    }
+
+    /// Returns the amount currently outstanding on the other networks, i.e.,
+    /// the upper bound of what can still be claimed from them.
+    pub fn net_exported(&self) -> U256 {
+        self.exported.saturating_sub(self.imported)
+    }
+}
+
+/// A commitment to the [`NativeSupply`] of each native token of the local
+/// network.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NativeSupplyTree<H>
+where
+    H: Hasher,
+    H::Digest: Serialize + DeserializeOwned,
+{
+    /// The Merkle Root of the native supply tree
+    #[serde_as(as = "_")]
+    pub root: H::Digest,
+}
+
+pub type NativeSupplyPath<H> = SmtMerkleProof<H, NATIVE_SUPPLY_TREE_DEPTH>;
+
+impl NativeSupplyTree<Keccak256Hasher> {
+    /// Returns the tree in which every native supply is zero.
+    pub fn empty() -> Self {
+        let root = (0..NATIVE_SUPPLY_TREE_DEPTH).fold(Digest::default(), |hash, _| {
+            Keccak256Hasher::merge(&hash, &hash)
+        });
+
+        Self { root }
+    }
+
+    pub fn verify_and_update(
+        &mut self,
+        key: TokenInfo,
+        path_to_update: &NativeSupplyPath<Keccak256Hasher>,
+        old_supply: &NativeSupply,
+        new_supply: &NativeSupply,
+    ) -> Result<(), ProofError> {
+        self.root = path_to_update
+            .verify_and_update(key, old_supply.hash(), new_supply.hash(), self.root)
+            .ok_or(ProofError::InvalidNativeSupplyPath)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    eip712::Eip712Domain, keccak::digest::Digest, local_state::NetworkState,
+    native_supply_tree::NativeSupplyTree, outflow_limits_tree::OutflowLimitsTree,
+};
+
+/// Configuration of one network set by the agglayer outside of the
+/// certificates, e.g. the outflow caps of its tokens, its freeze or its
+/// authorized signers.
+///
+/// A configuration change is not applied on the committed state directly:
+/// it is carried by the next [`MultiBatchHeader`] and applied before its
+/// bridge events, so that every pessimistic root is the output of a proof.
+///
+/// [`MultiBatchHeader`]: crate::multi_batch_header::MultiBatchHeader
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Root of the outflow limits tree. None if the outflows are not capped.
+    pub outflow_limits_root: Option<Digest>,
+    /// Whether the native supply is tracked. Once enabled, it is tracked from
+    /// zero for every native token.
+    pub native_supply_tracked: bool,
+    /// Commitment to the forced withdrawals pending on the network. None if
+    /// the network is not frozen.
+    pub forced_withdrawals_root: Option<Digest>,
+    /// Commitment to the signers authorized on the network. None if any
+    /// signer is accepted.
+    pub signer_set_root: Option<Digest>,
+    /// Domain of the EIP-712 signatures of the certificates. None if the
+    /// legacy commitment is signed.
+    pub eip712_domain: Option<Eip712Domain>,
+}
+
+impl NetworkConfig {
+    /// Replaces the configuration of the given state by this one.
+    pub fn apply(&self, state: &mut NetworkState) {
+        state.outflow_limits_tree = self
+            .outflow_limits_root
+            .map(|root| OutflowLimitsTree { root });
+        if !self.native_supply_tracked {
+            state.native_supply_tree = None;
+        } else if state.native_supply_tree.is_none() {
+            state.native_supply_tree = Some(NativeSupplyTree::empty());
+        }
+        state.forced_withdrawals_root = self.forced_withdrawals_root;
+        state.signer_set_root = self.signer_set_root;
+        state.eip712_domain = self.eip712_domain;
+    }
+}
@@ -0,0 +1,63 @@
+use std::hash::Hash;
+
+use agglayer_primitives::U256;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::{
+    bridge_exit::TokenInfo,
+    local_exit_tree::hasher::Hasher,
+    utils::{smt::SmtMerkleProof, FromU256},
+    ProofError,
+};
+
+/// The key is [`TokenInfo`] which can be packed into 192 bits (32 for network
+/// id and 160 for token address).
+pub const OUTFLOW_LIMITS_TREE_DEPTH: usize = 192;
+
+/// A commitment to the maximum amount of each token that can leave the local
+/// network within one certificate. A zero leaf means that the token is not
+/// capped.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutflowLimitsTree<H>
+where
+    H: Hasher,
+    H::Digest: Serialize + DeserializeOwned,
+{
+    /// The Merkle Root of the outflow limits tree
+    #[serde_as(as = "_")]
+    pub root: H::Digest,
+}
+
+pub type OutflowLimitPath<H> = SmtMerkleProof<H, OUTFLOW_LIMITS_TREE_DEPTH>;
+
+impl<H> OutflowLimitsTree<H>
+where
+    H: Hasher,
+    H::Digest: Copy + Eq + Hash + Default + Serialize + for<'a> Deserialize<'a> + FromU256,
+{
+    /// Verifies that the given limit is the one committed for the given token,
+    /// and that the outflow of the token does not exceed it.
+    pub fn verify_outflow(
+        &self,
+        key: TokenInfo,
+        path: &OutflowLimitPath<H>,
+        limit: U256,
+        outflow: U256,
+    ) -> Result<(), ProofError> {
+        if !path.verify(key, H::Digest::from_u256(limit), self.root) {
+            return Err(ProofError::InvalidOutflowLimitPath);
+        }
+
+        if !limit.is_zero() && outflow > limit {
+            return Err(ProofError::OutflowLimitExceeded {
+                token: key,
+                limit,
+                outflow,
+            });
+        }
+
+        Ok(())
+    }
+}
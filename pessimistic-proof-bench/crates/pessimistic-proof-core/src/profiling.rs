@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// Phases of the pessimistic proof reported to a [`Profiler`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Deserialization of the inputs, only reported by the guests.
+    Deserialization,
+    /// Checks of the previous roots against the initial state.
+    PrevRootChecks,
+    /// Verification of the inclusion proofs of the imported bridge exits.
+    ImportedExitPaths,
+    /// Non-inclusion checks and updates of the nullifier tree.
+    NullifierUpdates,
+    /// Verification and updates of the local balance tree.
+    BalanceUpdates,
+    /// Recovery of the signer of the state transition.
+    SignatureRecovery,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::Deserialization,
+        Phase::PrevRootChecks,
+        Phase::ImportedExitPaths,
+        Phase::NullifierUpdates,
+        Phase::BalanceUpdates,
+        Phase::SignatureRecovery,
+    ];
+
+    /// Returns the label of the phase, as reported to the cycle trackers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Deserialization => "deserialization",
+            Phase::PrevRootChecks => "prev_root_checks",
+            Phase::ImportedExitPaths => "imported_exit_paths",
+            Phase::NullifierUpdates => "nullifier_updates",
+            Phase::BalanceUpdates => "balance_updates",
+            Phase::SignatureRecovery => "signature_recovery",
+        }
+    }
+
+    /// Returns the phase of the given label.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|phase| phase.name() == name)
+    }
+}
+
+/// Hooks called around each occurrence of the [`Phase`]s of the pessimistic
+/// proof, so that the guests can attribute their cycles. The phases within
+/// loops are reported once per iteration. Does nothing by default.
+pub trait Profiler {
+    /// Called at the start of one occurrence of the given phase.
+    fn start(&mut self, _phase: Phase) {}
+
+    /// Called at the end of one occurrence of the given phase.
+    fn end(&mut self, _phase: Phase) {}
+
+    /// Runs the given operation as one occurrence of the given phase.
+    fn measure<T>(&mut self, phase: Phase, operation: impl FnOnce() -> T) -> T
+    where
+        Self: Sized,
+    {
+        self.start(phase);
+        let result = operation();
+        self.end(phase);
+        result
+    }
+}
+
+/// [`Profiler`] ignoring all the phases.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopProfiler;
+
+impl Profiler for NoopProfiler {}
@@ -1,17 +1,20 @@
-use agglayer_primitives::Address;
+use agglayer_primitives::{Address, U256};
 pub use bincode::Options;
 use hex_literal::hex;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    bridge_exit::{NetworkId, TokenInfo},
+    bridge_exit::{commit_message_exits, NetworkId, TokenInfo},
     global_index::GlobalIndex,
     imported_bridge_exit,
     keccak::{digest::Digest, keccak256_combine},
     local_exit_tree::{hasher::Keccak256Hasher, LocalExitTreeError},
-    local_state::{NetworkState, StateCommitment},
-    multi_batch_header::MultiBatchHeader,
+    local_state::NetworkState,
+    multi_batch_header::{CommitmentMode, MultiBatchHeader},
+    network_config::NetworkConfig,
+    profiling::{NoopProfiler, Profiler},
+    signer_set::{SignerRotation, SignerRotationEvent},
 };
 
 /// Represents all errors that can occur while generating the proof.
@@ -91,6 +94,87 @@ pub enum ProofError {
     /// The provided token comes with multiple balance proofs.
     #[error("Duplicate token in balance proofs. TokenInfo: {0:?}")]
     DuplicateTokenBalanceProof(TokenInfo),
+    /// The provided token is missing an outflow limit proof.
+    #[error("Missing outflow limit proof. TokenInfo: {0:?}")]
+    MissingOutflowLimitProof(TokenInfo),
+    /// The provided outflow limit path is invalid.
+    #[error("Invalid outflow limit path.")]
+    InvalidOutflowLimitPath,
+    /// The bridge exits of the given token exceed its outflow limit.
+    #[error("Outflow limit exceeded. TokenInfo: {token:?}, limit: {limit}, outflow: {outflow}")]
+    OutflowLimitExceeded {
+        token: TokenInfo,
+        limit: U256,
+        outflow: U256,
+    },
+    /// The cumulative outflow of the given token overflows.
+    #[error("Outflow overflow. TokenInfo: {0:?}")]
+    OutflowOverflow(TokenInfo),
+    /// The new outflow limits root declared by the agglayer does not match the
+    /// one computed by the prover.
+    #[error("Invalid new outflow limits root. declared: {declared:?}, computed: {computed:?}")]
+    InvalidNewOutflowLimitsRoot {
+        declared: Option<Digest>,
+        computed: Option<Digest>,
+    },
+    /// The provided native token is missing a native supply proof.
+    #[error("Missing native supply proof. TokenInfo: {0:?}")]
+    MissingNativeSupplyProof(TokenInfo),
+    /// The provided native supply path is invalid.
+    #[error("Invalid native supply path.")]
+    InvalidNativeSupplyPath,
+    /// The cumulative native supply of the given token overflows.
+    #[error("Native supply overflow. TokenInfo: {0:?}")]
+    NativeSupplyOverflow(TokenInfo),
+    /// The new native supply root declared by the agglayer does not match the
+    /// one computed by the prover.
+    #[error("Invalid new native supply root. declared: {declared:?}, computed: {computed:?}")]
+    InvalidNewNativeSupplyRoot {
+        declared: Option<Digest>,
+        computed: Option<Digest>,
+    },
+    /// The list of forced withdrawals does not match the commitment of the
+    /// frozen network.
+    #[error("Invalid forced withdrawals. declared: {declared}, computed: {computed}")]
+    InvalidForcedWithdrawals { declared: Digest, computed: Digest },
+    /// The bridge exit is not allowed while the network is frozen.
+    #[error("Bridge exit not covered by the forced withdrawals of the frozen network: {0}")]
+    BridgeExitWhileFrozen(Digest),
+    /// The new forced withdrawals root declared by the agglayer does not match
+    /// the one computed by the prover.
+    #[error("Invalid new forced withdrawals root. declared: {declared:?}, computed: {computed:?}")]
+    InvalidNewForcedWithdrawalsRoot {
+        declared: Option<Digest>,
+        computed: Option<Digest>,
+    },
+    /// The list of authorized signers does not match the committed signer
+    /// set.
+    #[error("Invalid authorized signer set. declared: {declared}, computed: {computed}")]
+    InvalidSignerSet { declared: Digest, computed: Digest },
+    /// The signer is not part of the authorized signer set.
+    #[error("Unauthorized signer: {0}")]
+    UnauthorizedSigner(Address),
+    /// The signer rotation is not signed by the replaced signer, or introduces
+    /// a signer which is already authorized.
+    #[error("Invalid signer rotation.")]
+    InvalidSignerRotation,
+    /// A signer rotation is provided while no signer set is committed.
+    #[error("Signer rotation provided without an authorized signer set.")]
+    UnexpectedSignerRotation,
+    /// The new signer set root declared by the agglayer does not match the one
+    /// computed by the prover.
+    #[error("Invalid new signer set root. declared: {declared:?}, computed: {computed:?}")]
+    InvalidNewSignerSetRoot {
+        declared: Option<Digest>,
+        computed: Option<Digest>,
+    },
+    /// The signature commits to another scheme or domain than the ones
+    /// configured for the network.
+    #[error("Invalid commitment mode. declared: {declared:?}, expected: {expected:?}")]
+    InvalidCommitmentMode {
+        declared: CommitmentMode,
+        expected: CommitmentMode,
+    },
     /// The signature on the state transition is invalid.
     #[error("Invalid signature.")]
     InvalidSignature,
@@ -119,6 +203,12 @@ pub struct PessimisticProofOutput {
     /// The l1 info root against which we prove the inclusion of the imported
     /// bridge exits.
     pub l1_info_root: Digest,
+    /// The number of leaves in the L1 info tree at the time of the l1 info
+    /// root. The imported bridge exits refer to leaves below this count.
+    pub l1_info_tree_leaf_count: u32,
+    /// The minimum timestamp of the L1 info tree leaves referred to by the
+    /// imported bridge exits. Zero if unconstrained.
+    pub min_l1_info_timestamp: u64,
     /// The origin network of the pessimistic proof.
     pub origin_network: NetworkId,
     /// The consensus hash.
@@ -128,6 +218,14 @@ pub struct PessimisticProofOutput {
     /// The new pessimistic root which commits to the balance and nullifier
     /// tree.
     pub new_pessimistic_root: Digest,
+    /// The commitment to the message exits of the state transition, each along
+    /// with its leaf index in the local exit tree.
+    pub message_exits_root: Digest,
+    /// The signer rotation applied by the state transition, if any.
+    pub signer_rotation: Option<SignerRotationEvent>,
+    /// The configuration set by the agglayer and applied by the state
+    /// transition, if any.
+    pub config_update: Option<NetworkConfig>,
 }
 
 impl PessimisticProofOutput {
@@ -140,6 +238,21 @@ impl PessimisticProofOutput {
 
 const PESSIMISTIC_CONSENSUS_TYPE: u32 = 0;
 
+/// Consensus type of the state transitions of a frozen network.
+const FROZEN_CONSENSUS_TYPE: u32 = 1;
+
+/// Returns the consensus hash committing to the given signer, within the
+/// domain of frozen networks if `frozen` is set.
+pub fn consensus_hash(frozen: bool, signer: Address) -> Digest {
+    let consensus_type = if frozen {
+        FROZEN_CONSENSUS_TYPE
+    } else {
+        PESSIMISTIC_CONSENSUS_TYPE
+    };
+
+    keccak256_combine([&consensus_type.to_be_bytes(), signer.as_slice()])
+}
+
 pub const EMPTY_LER: Digest = Digest(hex!(
     "27ae5ba08d7291c96c8cbddcc148bf48a6d68c7974b94356f53754ef6171d757"
 ));
@@ -154,31 +267,40 @@ pub fn generate_pessimistic_proof(
     initial_network_state: NetworkState,
     batch_header: &MultiBatchHeader<Keccak256Hasher>,
 ) -> Result<PessimisticProofOutput, ProofError> {
-    let StateCommitment {
-        exit_root: prev_ler,
-        ler_leaf_count: prev_ler_leaf_count,
-        balance_root: prev_lbr,
-        nullifier_root: prev_nr,
-    } = initial_network_state.roots();
-    let prev_pessimistic_root = keccak256_combine([
-        prev_lbr.as_slice(),
-        prev_nr.as_slice(),
-        prev_ler_leaf_count.to_le_bytes().as_slice(),
-    ]);
-
-    let consensus_hash = keccak256_combine([
-        &PESSIMISTIC_CONSENSUS_TYPE.to_be_bytes(),
-        batch_header.signer.as_slice(),
-    ]);
-
-    let new_pessimistic_root = keccak256_combine([
-        batch_header.target.balance_root.as_slice(),
-        batch_header.target.nullifier_root.as_slice(),
-        batch_header.target.ler_leaf_count.to_le_bytes().as_slice(),
-    ]);
+    generate_pessimistic_proof_with_profiler(initial_network_state, batch_header, &mut NoopProfiler)
+}
+
+/// Same as [`generate_pessimistic_proof`], reporting its phases to the given
+/// [`Profiler`].
+pub fn generate_pessimistic_proof_with_profiler(
+    initial_network_state: NetworkState,
+    batch_header: &MultiBatchHeader<Keccak256Hasher>,
+    profiler: &mut impl Profiler,
+) -> Result<PessimisticProofOutput, ProofError> {
+    let prev_roots = initial_network_state.roots();
+    let prev_ler = prev_roots.exit_root;
+    let prev_pessimistic_root = prev_roots.pessimistic_root();
+
+    let message_exits_root = commit_message_exits(
+        batch_header
+            .bridge_exits
+            .iter()
+            .zip(prev_roots.ler_leaf_count..)
+            .filter_map(|(exit, leaf_index)| Some((leaf_index, exit.as_message()?))),
+    );
+
+    // The transition is proven as frozen if the network is frozen once the
+    // configuration update is applied
+    let frozen = match &batch_header.config_update {
+        Some(config) => config.forced_withdrawals_root.is_some(),
+        None => prev_roots.forced_withdrawals_root.is_some(),
+    };
+    let consensus_hash = consensus_hash(frozen, batch_header.signer);
+
+    let new_pessimistic_root = batch_header.target.pessimistic_root();
 
     let mut network_state = initial_network_state;
-    let computed_target = network_state.apply_batch_header(batch_header)?;
+    let computed_target = network_state.apply_batch_header_with_profiler(batch_header, profiler)?;
 
     if computed_target.exit_root != batch_header.target.exit_root {
         return Err(ProofError::InvalidNewLocalExitRoot {
@@ -201,6 +323,34 @@ pub fn generate_pessimistic_proof(
         });
     }
 
+    if computed_target.outflow_limits_root != batch_header.target.outflow_limits_root {
+        return Err(ProofError::InvalidNewOutflowLimitsRoot {
+            declared: batch_header.target.outflow_limits_root,
+            computed: computed_target.outflow_limits_root,
+        });
+    }
+
+    if computed_target.native_supply_root != batch_header.target.native_supply_root {
+        return Err(ProofError::InvalidNewNativeSupplyRoot {
+            declared: batch_header.target.native_supply_root,
+            computed: computed_target.native_supply_root,
+        });
+    }
+
+    if computed_target.forced_withdrawals_root != batch_header.target.forced_withdrawals_root {
+        return Err(ProofError::InvalidNewForcedWithdrawalsRoot {
+            declared: batch_header.target.forced_withdrawals_root,
+            computed: computed_target.forced_withdrawals_root,
+        });
+    }
+
+    if computed_target.signer_set_root != batch_header.target.signer_set_root {
+        return Err(ProofError::InvalidNewSignerSetRoot {
+            declared: batch_header.target.signer_set_root,
+            computed: computed_target.signer_set_root,
+        });
+    }
+
     // NOTE: Hack to comply with the L1 contracts which assume `0x00..00` for the
     // empty roots of the different trees involved. Therefore, we do
     // one mapping of empty tree hash <> 0x00..0 on the public inputs.
@@ -224,9 +374,17 @@ pub fn generate_pessimistic_proof(
         prev_local_exit_root,
         prev_pessimistic_root,
         l1_info_root: batch_header.l1_info_root,
+        l1_info_tree_leaf_count: batch_header.l1_info_tree_leaf_count,
+        min_l1_info_timestamp: batch_header.min_l1_info_timestamp.unwrap_or_default(),
         origin_network: batch_header.origin_network,
         consensus_hash,
         new_local_exit_root: batch_header.target.exit_root,
         new_pessimistic_root,
+        message_exits_root,
+        signer_rotation: batch_header
+            .signer_rotation
+            .as_ref()
+            .map(SignerRotation::event),
+        config_update: batch_header.config_update.clone(),
     })
 }
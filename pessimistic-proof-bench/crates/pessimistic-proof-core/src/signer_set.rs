@@ -0,0 +1,132 @@
+use agglayer_primitives::{Address, Signature, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bridge_exit::NetworkId,
+    keccak::{digest::Digest, keccak256_combine},
+    ProofError,
+};
+
+/// Domain separation of the signer rotation commitment.
+const SIGNER_ROTATION_DOMAIN: &[u8] = b"AGGLAYER_SIGNER_ROTATION";
+
+/// Commits to the set of signers authorized to sign the state transitions of
+/// one network, along with its nonce. The nonce counts the changes of the set
+/// and never decreases, so that a set restored after some changes commits to
+/// another root than before.
+pub fn commit_signer_set(nonce: u64, signers: impl Iterator<Item = Address>) -> Digest {
+    let signers = keccak256_combine(signers);
+    keccak256_combine([nonce.to_be_bytes().as_slice(), signers.as_slice()])
+}
+
+/// Replacement of one authorized signer by a new one, authorized by the
+/// replaced signer.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignerRotation {
+    /// The authorized signer being replaced.
+    pub old_signer: Address,
+    /// The signer replacing it.
+    pub new_signer: Address,
+    /// Signature of the old signer on the rotation commitment.
+    pub signature: Signature,
+}
+
+/// The signer rotation applied by one state transition.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignerRotationEvent {
+    /// The signer which was replaced.
+    pub old_signer: Address,
+    /// The signer which replaced it.
+    pub new_signer: Address,
+}
+
+impl SignerRotation {
+    /// Returns the commitment signed by the old signer. It is bound to the
+    /// network and to the commitment of its current signer set, whose nonce
+    /// is incremented by every rotation, so that it cannot be replayed once
+    /// applied, even if the same signers are authorized again later.
+    pub fn commitment(
+        origin_network: NetworkId,
+        signer_set_root: Digest,
+        old_signer: Address,
+        new_signer: Address,
+    ) -> Digest {
+        keccak256_combine([
+            SIGNER_ROTATION_DOMAIN,
+            origin_network.to_be_bytes().as_slice(),
+            signer_set_root.as_slice(),
+            old_signer.as_slice(),
+            new_signer.as_slice(),
+        ])
+    }
+
+    /// Verifies that the rotation is signed by the old signer.
+    pub fn verify(
+        &self,
+        origin_network: NetworkId,
+        signer_set_root: Digest,
+    ) -> Result<(), ProofError> {
+        let commitment = Self::commitment(
+            origin_network,
+            signer_set_root,
+            self.old_signer,
+            self.new_signer,
+        );
+
+        let recovered = self
+            .signature
+            .recover_address_from_prehash(&B256::new(commitment.0))
+            .map_err(|_| ProofError::InvalidSignerRotation)?;
+
+        if recovered != self.old_signer {
+            return Err(ProofError::InvalidSignerRotation);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the event corresponding to this rotation.
+    pub fn event(&self) -> SignerRotationEvent {
+        SignerRotationEvent {
+            old_signer: self.old_signer,
+            new_signer: self.new_signer,
+        }
+    }
+}
+
+/// Applies the optional rotation on the given authorized signer set and its
+/// nonce, and checks that the signer is authorized. Returns the updated nonce
+/// and signer set.
+pub fn apply_signer_rotation(
+    origin_network: NetworkId,
+    mut nonce: u64,
+    mut signers: Vec<Address>,
+    rotation: Option<&SignerRotation>,
+    signer: Address,
+) -> Result<(u64, Vec<Address>), ProofError> {
+    if let Some(rotation) = rotation {
+        rotation.verify(
+            origin_network,
+            commit_signer_set(nonce, signers.iter().copied()),
+        )?;
+
+        if signers.contains(&rotation.new_signer) {
+            return Err(ProofError::InvalidSignerRotation);
+        }
+
+        let index = signers
+            .iter()
+            .position(|authorized| *authorized == rotation.old_signer)
+            .ok_or(ProofError::UnauthorizedSigner(rotation.old_signer))?;
+        signers[index] = rotation.new_signer;
+        nonce = nonce
+            .checked_add(1)
+            .ok_or(ProofError::InvalidSignerRotation)?;
+    }
+
+    if !signers.contains(&signer) {
+        return Err(ProofError::UnauthorizedSigner(signer));
+    }
+
+    Ok((nonce, signers))
+}
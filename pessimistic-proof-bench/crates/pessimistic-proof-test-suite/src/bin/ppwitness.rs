@@ -0,0 +1,47 @@
+//! Writes the witness file of the sample inputs, to be proven by the `ppgen`
+//! binaries with `--input`.
+
+use std::path::PathBuf;
+
+use anyhow::ensure;
+use clap::Parser;
+use pessimistic_proof_test_suite::sample_data::sample_inputs;
+
+/// The arguments for the witness generator.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct WitnessArgs {
+    /// The number of bridge exits.
+    #[clap(long, default_value = "10")]
+    n_exits: usize,
+
+    /// The number of imported bridge exits.
+    #[clap(long, default_value = "10")]
+    n_imported_exits: usize,
+
+    /// The optional path to the custom sample data.
+    #[clap(long)]
+    sample_path: Option<PathBuf>,
+
+    /// The witness file to write, as JSON if its extension is `json` and as
+    /// binary otherwise.
+    #[clap(long)]
+    output: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = WitnessArgs::parse();
+    ensure!(
+        args.n_exits <= args.n_imported_exits,
+        "The sample state cannot export {} bridge exit(s) from {} imported one(s)",
+        args.n_exits,
+        args.n_imported_exits
+    );
+
+    sample_inputs(args.n_exits, args.n_imported_exits, args.sample_path)
+        .witness()
+        .write(&args.output)?;
+    eprintln!("Wrote the witness to {:?}", args.output);
+
+    Ok(())
+}
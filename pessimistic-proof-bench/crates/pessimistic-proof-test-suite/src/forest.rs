@@ -5,11 +5,9 @@ pub use pessimistic_proof::bridge_exit::LeafType;
 use pessimistic_proof::{
     bridge_exit::{BridgeExit, TokenInfo},
     global_index::GlobalIndex,
-    imported_bridge_exit::{
-        Claim, ClaimFromMainnet, ImportedBridgeExit, L1InfoTreeLeaf, L1InfoTreeLeafInner,
-        MerkleProof,
-    },
+    imported_bridge_exit::{Claim, ClaimFromMainnet, ImportedBridgeExit, MerkleProof},
     keccak::{digest::Digest, keccak256, keccak256_combine},
+    l1_info_tree::L1InfoTree,
     local_exit_tree::{data::LocalExitTreeData, hasher::Keccak256Hasher, LocalExitTree},
     local_state::LocalNetworkState,
     utils::{smt::Smt, Hashable as _},
@@ -25,7 +23,7 @@ use super::sample_data::{NETWORK_A, NETWORK_B};
 #[derive(Clone)]
 pub struct Forest {
     pub wallet: LocalWallet,
-    pub l1_info_tree: LocalExitTreeData<Keccak256Hasher>,
+    pub l1_info_tree: L1InfoTree,
     pub local_exit_tree_data_a: LocalExitTreeData<Keccak256Hasher>,
     pub state_b: LocalNetworkStateData,
 }
@@ -42,6 +40,14 @@ impl Default for Forest {
                 exit_tree: LocalExitTree::new(),
                 balance_tree: local_balance_tree,
                 nullifier_tree: Smt::new(),
+                outflow_limits_tree: None,
+                native_supply: None,
+                forced_withdrawals: None,
+                authorized_signers: None,
+                signer_set_nonce: 0,
+                eip712_domain: None,
+                min_l1_info_timestamp: None,
+                proven_config: Default::default(),
             },
         }
     }
@@ -86,6 +92,14 @@ impl Forest {
                 exit_tree: local_exit_tree,
                 balance_tree: local_balance_tree,
                 nullifier_tree: Smt::new(),
+                outflow_limits_tree: None,
+                native_supply: None,
+                forced_withdrawals: None,
+                authorized_signers: None,
+                signer_set_nonce: 0,
+                eip712_domain: None,
+                min_l1_info_timestamp: None,
+                proven_config: Default::default(),
             },
         }
     }
@@ -107,23 +121,20 @@ impl Forest {
             self.local_exit_tree_data_a.add_leaf(exit.hash()).unwrap();
         }
 
-        let l1_leaf = L1InfoTreeLeaf {
-            l1_info_tree_index: 0,
-            rer: Digest::default(),
-            mer: self.local_exit_tree_data_a.get_root(),
-            inner: L1InfoTreeLeafInner {
-                block_hash: Digest::default(),
-                timestamp: 0,
-                global_exit_root: Digest::default(),
-            },
-        };
-
-        self.l1_info_tree.add_leaf(l1_leaf.hash()).unwrap();
-
-        let proof_ger_l1root = MerkleProof {
-            proof: self.l1_info_tree.get_proof(0).unwrap(),
-            root: self.l1_info_tree.get_root(),
-        };
+        let l1_leaf = self
+            .l1_info_tree
+            .append(
+                self.local_exit_tree_data_a.get_root(),
+                Digest::default(),
+                Digest::default(),
+                0,
+            )
+            .unwrap();
+
+        let proof_ger_l1root = self
+            .l1_info_tree
+            .get_proof(l1_leaf.l1_info_tree_index)
+            .unwrap();
 
         // Generate them as imported bridge exits
         for (idx, exit) in exits.into_iter().enumerate() {
@@ -189,6 +200,8 @@ impl Forest {
             imported_bridge_exits,
             signature,
             metadata: Default::default(),
+            signer_rotation: None,
+            commitment_mode: Default::default(),
         }
     }
 
@@ -204,11 +217,7 @@ impl Forest {
         );
         assert_eq!(
             output.new_pessimistic_root,
-            keccak256_combine([
-                self.state_b.balance_tree.root.as_slice(),
-                self.state_b.nullifier_tree.root.as_slice(),
-                self.state_b.exit_tree.leaf_count().to_le_bytes().as_slice(),
-            ])
+            self.state_b.get_roots().pessimistic_root()
         );
     }
 }
@@ -2,7 +2,7 @@
 
 use std::path::PathBuf;
 
-use agglayer_primitives::{address, U256};
+use agglayer_primitives::{address, Address, U256};
 use agglayer_types::{Certificate, NetworkId};
 use hex_literal::hex;
 use pessimistic_proof::bridge_exit::BridgeExit;
@@ -10,7 +10,10 @@ use pessimistic_proof::bridge_exit::TokenInfo;
 use pessimistic_proof::local_balance_tree::LocalBalanceTree;
 use pessimistic_proof::local_exit_tree;
 use pessimistic_proof::local_state::LocalNetworkState;
+use pessimistic_proof::multi_batch_header::MultiBatchHeader;
 use pessimistic_proof::nullifier_tree::NullifierTree;
+use pessimistic_proof::witness::Witness;
+use pessimistic_proof::NetworkState;
 
 use crate::{
     event_data::{load_json_data_file, parse_json_file, DepositEventData},
@@ -38,6 +41,11 @@ pub fn empty_state() -> LocalNetworkState {
         exit_tree: LocalExitTree::new(),
         balance_tree: LocalBalanceTree::new(),
         nullifier_tree: NullifierTree::new(),
+        outflow_limits_tree: None,
+        native_supply_tree: None,
+        forced_withdrawals_root: None,
+        signer_set_root: None,
+        eip712_domain: None,
     }
 }
 
@@ -107,3 +115,62 @@ pub fn sample_bridge_exits(sample_path: PathBuf) -> impl Iterator<Item = BridgeE
 pub fn load_certificate(cert_path: &str) -> Certificate {
     load_json_data_file::<Certificate>(cert_path)
 }
+
+/// Returns the token and amount of the first `n` sample bridge exits, taken
+/// from the given sample file or from the default one, cycling if needed.
+pub fn sample_events(n: usize, sample_path: Option<PathBuf>) -> Vec<(TokenInfo, U256)> {
+    let events: Box<dyn Iterator<Item = BridgeExit>> = match sample_path {
+        Some(path) => Box::new(sample_bridge_exits(path).cycle()),
+        None => Box::new(sample_bridge_exits_01().cycle()),
+    };
+
+    events
+        .take(n)
+        .map(|exit| (exit.token_info, exit.amount))
+        .collect()
+}
+
+/// Inputs of the pessimistic proof for one sample state transition.
+pub struct SampleInputs {
+    pub initial_state: NetworkState,
+    pub batch_header: MultiBatchHeader<TreeHasher>,
+    pub certificate: Certificate,
+    pub signer: Address,
+}
+
+impl SampleInputs {
+    /// Returns the [`Witness`] of these inputs, to be saved as a witness file.
+    pub fn witness(&self) -> Witness {
+        Witness::new(self.initial_state.clone(), self.batch_header.clone())
+    }
+}
+
+/// Builds the inputs of the pessimistic proof for a certificate importing and
+/// then exporting the given number of sample bridge exits. The sample state
+/// starts without any balance, so the bridge exits cannot outnumber the
+/// imported ones.
+pub fn sample_inputs(
+    n_exits: usize,
+    n_imported_exits: usize,
+    sample_path: Option<PathBuf>,
+) -> SampleInputs {
+    let mut forest = sample_state_00();
+    let initial_state = forest.state_b.clone();
+
+    let bridge_exits = sample_events(n_exits, sample_path.clone());
+    let imported_bridge_exits = sample_events(n_imported_exits, sample_path);
+    let certificate = forest.apply_events(&imported_bridge_exits, &bridge_exits);
+
+    let signer = forest.get_signer();
+    let l1_info_root = certificate.l1_info_root().unwrap().unwrap_or_default();
+    let batch_header = initial_state
+        .make_multi_batch_header(&certificate, signer, l1_info_root)
+        .unwrap();
+
+    SampleInputs {
+        initial_state: initial_state.into(),
+        batch_header,
+        certificate,
+        signer,
+    }
+}
@@ -1,11 +1,10 @@
 use std::{fmt::Display, ops::Deref};
 
 use agglayer_primitives::{Address, U256};
-pub use pessimistic_proof_core::bridge_exit::{LeafType, TokenInfo};
-use pessimistic_proof_core::{
-    bridge_exit::L1_ETH,
-    keccak::{digest::Digest, keccak256, keccak256_combine},
+pub use pessimistic_proof_core::bridge_exit::{
+    commit_message_exits, BridgeMessage, LeafType, TokenInfo, L1_ETH,
 };
+use pessimistic_proof_core::keccak::{digest::Digest, keccak256, keccak256_combine};
 use serde::{Deserialize, Serialize};
 
 use crate::utils::Hashable;
@@ -76,6 +75,27 @@ impl BridgeExit {
         }
     }
 
+    /// Creates a new message [`BridgeExit`] calling the given recipient
+    /// contract with the given calldata and ETH value.
+    pub fn new_message(
+        origin_network: NetworkId,
+        origin_address: Address,
+        dest_network: NetworkId,
+        recipient: Address,
+        value: U256,
+        calldata: &[u8],
+    ) -> Self {
+        Self::new(
+            LeafType::Message,
+            origin_network,
+            origin_address,
+            dest_network,
+            recipient,
+            value,
+            calldata.to_vec(),
+        )
+    }
+
     pub fn is_transfer(&self) -> bool {
         self.leaf_type == LeafType::Transfer
     }
@@ -108,6 +128,27 @@ impl BridgeExit {
             LeafType::Transfer => self.token_info,
         }
     }
+
+    /// Returns the [`BridgeMessage`] if the bridge exit is a message.
+    pub fn as_message(&self) -> Option<BridgeMessage> {
+        pessimistic_proof_core::bridge_exit::BridgeExit::from(self.clone()).as_message()
+    }
+}
+
+impl From<BridgeMessage> for BridgeExit {
+    fn from(message: BridgeMessage) -> Self {
+        Self {
+            leaf_type: LeafType::Message,
+            token_info: TokenInfo {
+                origin_network: message.origin_network,
+                origin_token_address: message.origin_address,
+            },
+            dest_network: message.dest_network.into(),
+            dest_address: message.recipient,
+            amount: message.value,
+            metadata: Some(message.calldata_hash),
+        }
+    }
 }
 
 #[derive(
@@ -187,4 +228,35 @@ mod tests {
             hex::encode(dm_root)
         );
     }
+
+    #[test]
+    fn message_round_trip() {
+        let calldata = b"transfer(address,uint256)";
+        let message_exit = BridgeExit::new_message(
+            1.into(),
+            Address::new([0x11; 20]),
+            2.into(),
+            Address::new([0x22; 20]),
+            U256::from(42),
+            calldata,
+        );
+
+        let message = message_exit.as_message().unwrap();
+        assert_eq!(message.recipient, Address::new([0x22; 20]));
+        assert_eq!(message.value, U256::from(42));
+        assert_eq!(message.calldata_hash, keccak256(calldata));
+        assert_eq!(message_exit.amount_token_info(), L1_ETH);
+        assert_eq!(BridgeExit::from(message).hash(), message_exit.hash());
+
+        let transfer = BridgeExit::new(
+            LeafType::Transfer,
+            1.into(),
+            Address::new([0x11; 20]),
+            2.into(),
+            Address::new([0x22; 20]),
+            U256::from(42),
+            calldata.to_vec(),
+        );
+        assert_eq!(transfer.as_message(), None);
+    }
 }
@@ -1,5 +1,5 @@
 pub use pessimistic_proof_core::imported_bridge_exit::{
-    commit_imported_bridge_exits, Claim, ClaimFromMainnet, ClaimFromRollup, L1InfoTreeLeaf,
+    commit_imported_bridge_exits, Claim, ClaimFromMainnet, ClaimFromRollup, Error, L1InfoTreeLeaf,
     L1InfoTreeLeafInner, MerkleProof,
 };
 use pessimistic_proof_core::{
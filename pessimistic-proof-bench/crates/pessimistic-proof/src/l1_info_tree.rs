@@ -0,0 +1,401 @@
+//! Host-side construction of the trees against which the imported bridge exits
+//! are proven: the rollup exit tree, the global exit root, and the L1 info
+//! tree.
+
+pub use pessimistic_proof_core::imported_bridge_exit::compute_global_exit_root;
+use pessimistic_proof_core::{
+    imported_bridge_exit::{L1InfoTreeLeaf, L1InfoTreeLeafInner, MerkleProof},
+    keccak::digest::Digest,
+    local_exit_tree::{hasher::Keccak256Hasher, LocalExitTreeError},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::local_exit_tree::data::LocalExitTreeData;
+
+/// The rollup exit tree, whose leaf at index `rollup_index` is the local exit
+/// root of the corresponding rollup.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RollupExitTree {
+    tree: LocalExitTreeData<Keccak256Hasher>,
+}
+
+impl RollupExitTree {
+    /// Creates a new empty [`RollupExitTree`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the local exit root of the given rollup. Missing rollups before
+    /// `rollup_index` are considered with an empty (zero) leaf.
+    pub fn update(
+        &mut self,
+        rollup_index: u32,
+        local_exit_root: Digest,
+    ) -> Result<(), LocalExitTreeError> {
+        while self.tree.layers[0].len() <= rollup_index as usize {
+            self.tree.add_leaf(Digest::default())?;
+        }
+
+        self.tree.set_leaf(rollup_index, local_exit_root)
+    }
+
+    /// Returns the local exit root registered for the given rollup, if any.
+    pub fn local_exit_root(&self, rollup_index: u32) -> Option<Digest> {
+        self.tree.layers[0].get(rollup_index as usize).copied()
+    }
+
+    /// Returns the rollup exit root.
+    pub fn get_root(&self) -> Digest {
+        self.tree.get_root()
+    }
+
+    /// Returns the inclusion proof of the given rollup local exit root in the
+    /// rollup exit root.
+    pub fn get_proof(&self, rollup_index: u32) -> Result<MerkleProof, LocalExitTreeError> {
+        Ok(MerkleProof {
+            proof: self.tree.get_proof(rollup_index)?,
+            root: self.get_root(),
+        })
+    }
+}
+
+/// The L1 info tree, whose leaves are the [`L1InfoTreeLeaf`] appended upon
+/// each update of the global exit root.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct L1InfoTree {
+    tree: LocalExitTreeData<Keccak256Hasher>,
+}
+
+impl L1InfoTree {
+    /// Creates a new empty [`L1InfoTree`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a new leaf committing to the global exit root derived from the
+    /// given mainnet and rollup exit roots. Returns the appended leaf.
+    pub fn append(
+        &mut self,
+        mainnet_exit_root: Digest,
+        rollup_exit_root: Digest,
+        block_hash: Digest,
+        timestamp: u64,
+    ) -> Result<L1InfoTreeLeaf, LocalExitTreeError> {
+        let inner = L1InfoTreeLeafInner {
+            global_exit_root: compute_global_exit_root(mainnet_exit_root, rollup_exit_root),
+            block_hash,
+            timestamp,
+        };
+        let l1_info_tree_index = self.tree.add_leaf(inner.hash())?;
+
+        Ok(L1InfoTreeLeaf {
+            l1_info_tree_index,
+            rer: rollup_exit_root,
+            mer: mainnet_exit_root,
+            inner,
+        })
+    }
+
+    /// Returns the number of leaves in the tree.
+    pub fn leaf_count(&self) -> u32 {
+        self.tree.layers[0].len() as u32
+    }
+
+    /// Returns the L1 info root.
+    pub fn get_root(&self) -> Digest {
+        self.tree.get_root()
+    }
+
+    /// Returns the inclusion proof of the given leaf in the current L1 info
+    /// root.
+    pub fn get_proof(&self, l1_info_tree_index: u32) -> Result<MerkleProof, LocalExitTreeError> {
+        Ok(MerkleProof {
+            proof: self.tree.get_proof(l1_info_tree_index)?,
+            root: self.get_root(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agglayer_primitives::{Address, U256};
+    use pessimistic_proof_core::{
+        global_index::GlobalIndex,
+        imported_bridge_exit::{Claim, ClaimFromMainnet, ClaimFromRollup, Error},
+        keccak::keccak256_combine,
+    };
+    use rand::random;
+
+    use super::*;
+    use crate::{
+        bridge_exit::{BridgeExit, LeafType},
+        imported_bridge_exit::ImportedBridgeExit,
+        utils::Hashable as _,
+    };
+
+    fn exit_leaves(n: usize) -> Vec<Digest> {
+        (0..n)
+            .map(|i| {
+                BridgeExit::new(
+                    LeafType::Transfer,
+                    0.into(),
+                    Address::default(),
+                    1.into(),
+                    random::<[u8; 20]>().into(),
+                    U256::from(i + 1),
+                    vec![],
+                )
+                .hash()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn l1_info_leaf_commits_to_ger() {
+        let mer: Digest = random();
+        let rer: Digest = random();
+        let mut l1_info_tree = L1InfoTree::new();
+        l1_info_tree
+            .append(random(), random(), random(), 1)
+            .unwrap();
+        let leaf = l1_info_tree.append(mer, rer, random(), 2).unwrap();
+
+        assert_eq!(leaf.l1_info_tree_index, 1);
+        assert_eq!(leaf.inner.global_exit_root, keccak256_combine([mer, rer]));
+        assert_eq!(l1_info_tree.leaf_count(), 2);
+
+        let proof = l1_info_tree.get_proof(leaf.l1_info_tree_index).unwrap();
+        assert!(proof.verify(leaf.hash(), leaf.l1_info_tree_index));
+    }
+
+    #[test]
+    fn l1_info_leaf_bounds() {
+        let mut l1_info_tree = L1InfoTree::new();
+        l1_info_tree
+            .append(random(), random(), random(), 10)
+            .unwrap();
+        let leaf = l1_info_tree
+            .append(random(), random(), random(), 20)
+            .unwrap();
+
+        let leaf_count = l1_info_tree.leaf_count();
+        assert_eq!(leaf.verify_bounds(leaf_count, None), Ok(()));
+        assert_eq!(leaf.verify_bounds(leaf_count, Some(20)), Ok(()));
+        assert_eq!(
+            leaf.verify_bounds(leaf_count - 1, None),
+            Err(Error::L1InfoTreeIndexOutOfBounds {
+                index: 1,
+                leaf_count: 1
+            })
+        );
+        assert_eq!(
+            leaf.verify_bounds(leaf_count, Some(21)),
+            Err(Error::StaleL1InfoTreeLeaf {
+                timestamp: 20,
+                min_timestamp: 21
+            })
+        );
+    }
+
+    #[test]
+    fn rollup_exit_tree_update_matches_rebuild() {
+        let lers: Vec<Digest> = (0..5).map(|_| random()).collect();
+
+        let mut updated = RollupExitTree::new();
+        updated.update(4, random()).unwrap();
+        for (rollup_index, ler) in lers.iter().enumerate() {
+            updated.update(rollup_index as u32, *ler).unwrap();
+        }
+
+        let rebuilt = LocalExitTreeData::<Keccak256Hasher>::from_leaves(lers.into_iter()).unwrap();
+        assert_eq!(updated.get_root(), rebuilt.get_root());
+    }
+
+    #[test]
+    fn rollup_exit_tree_pads_missing_rollups() {
+        let ler: Digest = random();
+        let mut tree = RollupExitTree::new();
+        tree.update(3, ler).unwrap();
+
+        assert_eq!(tree.local_exit_root(0), Some(Digest::default()));
+        assert_eq!(tree.local_exit_root(3), Some(ler));
+        assert_eq!(tree.local_exit_root(4), None);
+        assert!(tree.get_proof(3).unwrap().verify(ler, 3));
+    }
+
+    #[test]
+    fn claims_verify_against_built_trees() {
+        let rollup_index = 2;
+        let leaf_index = 3;
+
+        let leaves = exit_leaves(5);
+        let rollup_let =
+            LocalExitTreeData::<Keccak256Hasher>::from_leaves(leaves.iter().copied()).unwrap();
+        let mainnet_let =
+            LocalExitTreeData::<Keccak256Hasher>::from_leaves(leaves.iter().copied()).unwrap();
+
+        let mut rollup_exit_tree = RollupExitTree::new();
+        rollup_exit_tree.update(0, random()).unwrap();
+        rollup_exit_tree
+            .update(rollup_index, rollup_let.get_root())
+            .unwrap();
+
+        let mut l1_info_tree = L1InfoTree::new();
+        l1_info_tree
+            .append(random(), random(), random(), 0)
+            .unwrap();
+        let l1_leaf = l1_info_tree
+            .append(
+                mainnet_let.get_root(),
+                rollup_exit_tree.get_root(),
+                random(),
+                1,
+            )
+            .unwrap();
+        let proof_ger_l1root = l1_info_tree.get_proof(l1_leaf.l1_info_tree_index).unwrap();
+
+        let claim_from_rollup = ClaimFromRollup {
+            proof_leaf_ler: MerkleProof {
+                proof: rollup_let.get_proof(leaf_index).unwrap(),
+                root: rollup_let.get_root(),
+            },
+            proof_ler_rer: rollup_exit_tree.get_proof(rollup_index).unwrap(),
+            proof_ger_l1root: proof_ger_l1root.clone(),
+            l1_leaf: l1_leaf.clone(),
+        };
+        let rollup_global_index = GlobalIndex {
+            mainnet_flag: false,
+            rollup_index,
+            leaf_index,
+        };
+        assert_eq!(
+            claim_from_rollup.verify(
+                leaves[leaf_index as usize],
+                rollup_global_index,
+                l1_info_tree.get_root()
+            ),
+            Ok(())
+        );
+
+        let claim_from_mainnet = ClaimFromMainnet {
+            proof_leaf_mer: MerkleProof {
+                proof: mainnet_let.get_proof(leaf_index).unwrap(),
+                root: mainnet_let.get_root(),
+            },
+            proof_ger_l1root,
+            l1_leaf,
+        };
+        let mainnet_global_index = GlobalIndex {
+            mainnet_flag: true,
+            rollup_index: 0,
+            leaf_index,
+        };
+        assert_eq!(
+            claim_from_mainnet.verify(
+                leaves[leaf_index as usize],
+                mainnet_global_index,
+                l1_info_tree.get_root()
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn claim_with_inconsistent_ger_is_rejected() {
+        let leaf_index = 1;
+
+        let leaves = exit_leaves(2);
+        let mainnet_let =
+            LocalExitTreeData::<Keccak256Hasher>::from_leaves(leaves.iter().copied()).unwrap();
+
+        let mut l1_info_tree = L1InfoTree::new();
+        let mut l1_leaf = l1_info_tree
+            .append(mainnet_let.get_root(), random(), random(), 0)
+            .unwrap();
+        let proof_ger_l1root = l1_info_tree.get_proof(l1_leaf.l1_info_tree_index).unwrap();
+
+        // Declare a RER which is not the one committed in the GER
+        l1_leaf.rer = random();
+
+        let claim_from_mainnet = ClaimFromMainnet {
+            proof_leaf_mer: MerkleProof {
+                proof: mainnet_let.get_proof(leaf_index).unwrap(),
+                root: mainnet_let.get_root(),
+            },
+            proof_ger_l1root,
+            l1_leaf,
+        };
+        let global_index = GlobalIndex {
+            mainnet_flag: true,
+            rollup_index: 0,
+            leaf_index,
+        };
+        assert_eq!(
+            claim_from_mainnet.verify(
+                leaves[leaf_index as usize],
+                global_index,
+                l1_info_tree.get_root()
+            ),
+            Err(Error::MismatchGlobalExitRoot)
+        );
+    }
+
+    #[test]
+    fn mainnet_claim_with_rollup_index_is_rejected() {
+        let leaf_index = 1;
+
+        let exits: Vec<BridgeExit> = (0..2)
+            .map(|i| {
+                BridgeExit::new(
+                    LeafType::Transfer,
+                    0.into(),
+                    Address::default(),
+                    1.into(),
+                    random::<[u8; 20]>().into(),
+                    U256::from(i + 1),
+                    vec![],
+                )
+            })
+            .collect();
+        let mainnet_let =
+            LocalExitTreeData::<Keccak256Hasher>::from_leaves(exits.iter().map(|e| e.hash()))
+                .unwrap();
+
+        let mut l1_info_tree = L1InfoTree::new();
+        let l1_leaf = l1_info_tree
+            .append(mainnet_let.get_root(), random(), random(), 0)
+            .unwrap();
+
+        let imported_bridge_exit = ImportedBridgeExit::new(
+            exits[leaf_index as usize].clone(),
+            Claim::Mainnet(Box::new(ClaimFromMainnet {
+                proof_leaf_mer: MerkleProof {
+                    proof: mainnet_let.get_proof(leaf_index).unwrap(),
+                    root: mainnet_let.get_root(),
+                },
+                proof_ger_l1root: l1_info_tree.get_proof(l1_leaf.l1_info_tree_index).unwrap(),
+                l1_leaf,
+            })),
+            GlobalIndex {
+                mainnet_flag: true,
+                rollup_index: 0,
+                leaf_index,
+            },
+        );
+        let mut imported_bridge_exit =
+            pessimistic_proof_core::imported_bridge_exit::ImportedBridgeExit::from(
+                imported_bridge_exit,
+            );
+        assert_eq!(
+            imported_bridge_exit.verify_path(l1_info_tree.get_root()),
+            Ok(())
+        );
+
+        // Same leaf, but with another encoding of the global index
+        imported_bridge_exit.global_index.rollup_index = random::<u32>().max(1);
+        assert_eq!(
+            imported_bridge_exit.verify_path(l1_info_tree.get_root()),
+            Err(Error::NonCanonicalGlobalIndex)
+        );
+    }
+}
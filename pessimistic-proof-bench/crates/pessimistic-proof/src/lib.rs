@@ -9,9 +9,12 @@ pub mod bridge_exit;
 
 pub mod global_index;
 pub mod imported_bridge_exit;
+pub mod l1_info_tree;
 pub mod local_state;
+pub mod native_supply;
 pub mod nullifier_tree;
 pub mod utils;
+pub mod witness;
 
 pub use local_state::LocalNetworkState;
 
@@ -19,10 +22,18 @@ pub mod keccak {
     pub use pessimistic_proof_core::keccak::*;
 }
 
+pub use pessimistic_proof_core::eip712;
+pub use pessimistic_proof_core::forced_withdrawals;
 pub use pessimistic_proof_core::local_state::NetworkState;
 pub use pessimistic_proof_core::multi_batch_header;
+pub use pessimistic_proof_core::network_config;
+pub use pessimistic_proof_core::outflow_limits_tree;
+pub use pessimistic_proof_core::profiling;
 pub use pessimistic_proof_core::proof::ProofError;
+pub use pessimistic_proof_core::signer_set;
 
 pub mod core {
-    pub use pessimistic_proof_core::generate_pessimistic_proof;
+    pub use pessimistic_proof_core::{
+        generate_pessimistic_proof, generate_pessimistic_proof_with_profiler,
+    };
 }
@@ -71,6 +71,31 @@ where
             return Err(LocalExitTreeError::LeafIndexOverflow);
         }
         self.layers[0].push(leaf);
+        self.update_path(leaf_index, leaf)?;
+
+        leaf_index
+            .try_into()
+            .map_err(|_| LocalExitTreeError::LeafIndexOverflow)
+    }
+
+    /// Replaces an already inserted leaf and updates its path to the root.
+    pub fn set_leaf(&mut self, leaf_index: u32, leaf: H::Digest) -> Result<(), LocalExitTreeError> {
+        let leaf_index: usize = leaf_index
+            .try_into()
+            .map_err(|_| LocalExitTreeError::LeafIndexOverflow)?;
+        if leaf_index >= self.layers[0].len() {
+            return Err(LocalExitTreeError::IndexOutOfBounds);
+        }
+        self.layers[0][leaf_index] = leaf;
+        self.update_path(leaf_index, leaf)
+    }
+
+    /// Recomputes the intermediate nodes from the given leaf up to the root.
+    fn update_path(
+        &mut self,
+        leaf_index: usize,
+        leaf: H::Digest,
+    ) -> Result<(), LocalExitTreeError> {
         let mut index = leaf_index;
         let mut entry = leaf;
         for height in 0..TREE_DEPTH - 1 {
@@ -88,9 +113,7 @@ where
             }
         }
 
-        leaf_index
-            .try_into()
-            .map_err(|_| LocalExitTreeError::LeafIndexOverflow)
+        Ok(())
     }
 
     pub fn get(&self, height: usize, index: usize) -> Result<H::Digest, LocalExitTreeError> {
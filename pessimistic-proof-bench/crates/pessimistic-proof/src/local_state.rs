@@ -1,9 +1,12 @@
-use pessimistic_proof_core::{keccak::digest::Digest, local_exit_tree::hasher::Keccak256Hasher};
+use pessimistic_proof_core::{
+    eip712::Eip712Domain, keccak::digest::Digest, local_exit_tree::hasher::Keccak256Hasher,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     local_balance_tree::LocalBalanceTree, local_exit_tree::LocalExitTree,
-    nullifier_tree::NullifierTree,
+    native_supply::NativeSupplyTree, nullifier_tree::NullifierTree,
+    outflow_limits_tree::OutflowLimitsTree,
 };
 
 /// State representation of one network without the leaves, taken as input by
@@ -17,6 +20,21 @@ pub struct LocalNetworkState {
     /// Commitment to the Nullifier tree for the local network, tracks claimed
     /// assets on foreign networks
     pub nullifier_tree: NullifierTree<Keccak256Hasher>,
+    /// Commitment to the per-certificate outflow limit of each token. None if
+    /// the outflows are not capped.
+    pub outflow_limits_tree: Option<OutflowLimitsTree<Keccak256Hasher>>,
+    /// Commitment to the cumulative exported and imported amounts of each
+    /// native token. None if the native supply is not tracked.
+    pub native_supply_tree: Option<NativeSupplyTree<Keccak256Hasher>>,
+    /// Commitment to the forced withdrawals pending on the network while it is
+    /// frozen. None if the network is not frozen.
+    pub forced_withdrawals_root: Option<Digest>,
+    /// Commitment to the set of signers authorized to sign the state
+    /// transitions. None if any signer is accepted.
+    pub signer_set_root: Option<Digest>,
+    /// Domain of the EIP-712 signatures of the state transitions. None if the
+    /// legacy commitment is signed.
+    pub eip712_domain: Option<Eip712Domain>,
 }
 
 impl From<LocalNetworkState> for pessimistic_proof_core::NetworkState {
@@ -25,6 +43,11 @@ impl From<LocalNetworkState> for pessimistic_proof_core::NetworkState {
             exit_tree: state.exit_tree.into(),
             balance_tree: state.balance_tree.into(),
             nullifier_tree: state.nullifier_tree.into(),
+            outflow_limits_tree: state.outflow_limits_tree,
+            native_supply_tree: state.native_supply_tree,
+            forced_withdrawals_root: state.forced_withdrawals_root,
+            signer_set_root: state.signer_set_root,
+            eip712_domain: state.eip712_domain,
         }
     }
 }
@@ -36,15 +59,35 @@ pub struct StateCommitment {
     pub ler_leaf_count: u32,
     pub balance_root: Digest,
     pub nullifier_root: Digest,
+    pub outflow_limits_root: Option<Digest>,
+    pub native_supply_root: Option<Digest>,
+    pub forced_withdrawals_root: Option<Digest>,
+    pub signer_set_root: Option<Digest>,
+    pub eip712_domain_separator: Option<Digest>,
 }
 
 impl StateCommitment {
     pub fn display_to_hex(&self) -> String {
         format!(
-            "exit_root: {}, ler_leaf_count: {}, balance_root: {}, nullifier_root: {}",
-            self.exit_root, self.ler_leaf_count, self.balance_root, self.nullifier_root,
+            "exit_root: {}, ler_leaf_count: {}, balance_root: {}, nullifier_root: {}, \
+             outflow_limits_root: {:?}, native_supply_root: {:?}, forced_withdrawals_root: {:?}, \
+             signer_set_root: {:?}, eip712_domain_separator: {:?}",
+            self.exit_root,
+            self.ler_leaf_count,
+            self.balance_root,
+            self.nullifier_root,
+            self.outflow_limits_root,
+            self.native_supply_root,
+            self.forced_withdrawals_root,
+            self.signer_set_root,
+            self.eip712_domain_separator,
         )
     }
+
+    /// Returns the pessimistic root committing to this state.
+    pub fn pessimistic_root(&self) -> Digest {
+        pessimistic_proof_core::local_state::StateCommitment::from(self.clone()).pessimistic_root()
+    }
 }
 
 impl From<StateCommitment> for pessimistic_proof_core::local_state::StateCommitment {
@@ -54,6 +97,11 @@ impl From<StateCommitment> for pessimistic_proof_core::local_state::StateCommitm
             ler_leaf_count: commitment.ler_leaf_count,
             balance_root: commitment.balance_root,
             nullifier_root: commitment.nullifier_root,
+            outflow_limits_root: commitment.outflow_limits_root,
+            native_supply_root: commitment.native_supply_root,
+            forced_withdrawals_root: commitment.forced_withdrawals_root,
+            signer_set_root: commitment.signer_set_root,
+            eip712_domain_separator: commitment.eip712_domain_separator,
         }
     }
 }
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+pub use pessimistic_proof_core::native_supply_tree::{
+    NativeSupply, NativeSupplyPath, NativeSupplyTree, NATIVE_SUPPLY_TREE_DEPTH,
+};
+use pessimistic_proof_core::{
+    bridge_exit::TokenInfo, keccak::digest::Digest, local_exit_tree::hasher::Keccak256Hasher,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::utils::smt::{Smt, SmtError};
+
+/// Full native supply tree, along with the [`NativeSupply`] of each leaf.
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NativeSupplyLedger {
+    /// The full native supply tree.
+    pub tree: Smt<Keccak256Hasher, NATIVE_SUPPLY_TREE_DEPTH>,
+    /// The native supply of each tracked token.
+    #[serde_as(as = "Vec<(_, _)>")]
+    supplies: BTreeMap<TokenInfo, NativeSupply>,
+}
+
+impl NativeSupplyLedger {
+    /// Creates a new empty [`NativeSupplyLedger`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the native supply root.
+    pub fn root(&self) -> Digest {
+        self.tree.root
+    }
+
+    /// Returns the native supply of the given token.
+    pub fn get(&self, token: TokenInfo) -> NativeSupply {
+        self.supplies.get(&token).copied().unwrap_or_default()
+    }
+
+    /// Returns the native supply of all the tracked tokens.
+    pub fn iter(&self) -> impl Iterator<Item = (&TokenInfo, &NativeSupply)> {
+        self.supplies.iter()
+    }
+
+    /// Sets the native supply of the given token. Returns the previous supply
+    /// along with its inclusion proof in the previous root.
+    pub fn update(
+        &mut self,
+        token: TokenInfo,
+        new_supply: NativeSupply,
+    ) -> Result<(NativeSupply, NativeSupplyPath<Keccak256Hasher>), SmtError> {
+        let old_supply = self.get(token);
+        let path = if old_supply == NativeSupply::default() {
+            self.tree.get_inclusion_proof_zero(token)?
+        } else {
+            self.tree.get_inclusion_proof(token)?
+        };
+
+        self.tree.update(token, new_supply.hash())?;
+        self.supplies.insert(token, new_supply);
+
+        Ok((old_supply, path))
+    }
+}
+
+impl From<&NativeSupplyLedger> for NativeSupplyTree<Keccak256Hasher> {
+    fn from(ledger: &NativeSupplyLedger) -> Self {
+        Self {
+            root: ledger.root(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agglayer_primitives::{Address, U256};
+    use rand::random;
+
+    use super::*;
+
+    #[test]
+    fn ledger_update_proves_previous_supply() {
+        let token = TokenInfo {
+            origin_network: random(),
+            origin_token_address: Address::new(random()),
+        };
+        let mut ledger = NativeSupplyLedger::new();
+        let empty_root = ledger.root();
+        assert_eq!(NativeSupplyTree::empty().root, empty_root);
+
+        let first = NativeSupply {
+            exported: U256::from(random::<u64>()),
+            imported: U256::ZERO,
+        };
+        let (old_supply, path) = ledger.update(token, first).unwrap();
+        assert_eq!(old_supply, NativeSupply::default());
+        assert!(path.verify(token, Digest::default(), empty_root));
+
+        let root = ledger.root();
+        let second = NativeSupply {
+            imported: U256::from(1),
+            ..first
+        };
+        let (old_supply, path) = ledger.update(token, second).unwrap();
+        assert_eq!(old_supply, first);
+        assert!(path.verify(token, first.hash(), root));
+        assert_eq!(ledger.get(token), second);
+        assert_eq!(ledger.iter().count(), 1);
+    }
+}
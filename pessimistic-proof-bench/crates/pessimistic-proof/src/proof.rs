@@ -1,4 +1,4 @@
-pub use pessimistic_proof_core::PessimisticProofOutput;
+pub use pessimistic_proof_core::{proof::consensus_hash, PessimisticProofOutput};
 
 pub trait DisplayToHex {
     fn display_to_hex(&self) -> String;
@@ -8,15 +8,21 @@ impl DisplayToHex for PessimisticProofOutput {
     fn display_to_hex(&self) -> String {
         format!(
             "prev_local_exit_root: {}, prev_pessimistic_root: {}, l1_info_root: {}, \
-             origin_network: {}, consensus_hash: {}, new_local_exit_root: {}, \
-             new_pessimistic_root: {}",
+             l1_info_tree_leaf_count: {}, min_l1_info_timestamp: {}, origin_network: {}, \
+             consensus_hash: {}, new_local_exit_root: {}, new_pessimistic_root: {}, \
+             message_exits_root: {}, signer_rotation: {:?}, config_update: {:?}",
             self.prev_local_exit_root,
             self.prev_pessimistic_root,
             self.l1_info_root,
+            self.l1_info_tree_leaf_count,
+            self.min_l1_info_timestamp,
             self.origin_network,
             self.consensus_hash,
             self.new_local_exit_root,
             self.new_pessimistic_root,
+            self.message_exits_root,
+            self.signer_rotation,
+            self.config_update,
         )
     }
 }
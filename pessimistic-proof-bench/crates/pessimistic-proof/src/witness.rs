@@ -0,0 +1,140 @@
+//! Witness files, capturing the inputs of the pessimistic proof so that they
+//! can be proven again by any backend.
+//!
+//! A witness file is either JSON, as `{"version": 3, "witness": {...}}`, or
+//! binary, as [`WITNESS_MAGIC`] followed by the bincode encoding of the same
+//! envelope. The format is detected when loading.
+
+use std::path::Path;
+
+use pessimistic_proof_core::{
+    local_exit_tree::hasher::Keccak256Hasher, local_state::NetworkState,
+    multi_batch_header::MultiBatchHeader,
+};
+use serde::{Deserialize, Serialize};
+
+/// Version of the witness files written by this crate.
+pub const WITNESS_VERSION: u32 = 3;
+
+/// Prefix of the binary witness files.
+pub const WITNESS_MAGIC: &[u8; 8] = b"ppwitnes";
+
+#[derive(Debug, thiserror::Error)]
+pub enum WitnessError {
+    /// The witness file cannot be read or written.
+    #[error("Witness I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The binary witness cannot be encoded or decoded.
+    #[error("Binary witness serialization error: {0}")]
+    Binary(#[from] bincode::Error),
+    /// The JSON witness cannot be encoded or decoded.
+    #[error("JSON witness serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The witness was written by an incompatible version.
+    #[error("Unsupported witness version. expected: {WITNESS_VERSION}, got: {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// Format of the witness files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessFormat {
+    Binary,
+    Json,
+}
+
+impl WitnessFormat {
+    /// Returns the format of the given file, JSON if its extension is `json`
+    /// and binary otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => WitnessFormat::Json,
+            _ => WitnessFormat::Binary,
+        }
+    }
+}
+
+/// Inputs of the pessimistic proof.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Witness {
+    /// The state of the network before the transition.
+    pub initial_state: NetworkState,
+    /// The transition to prove.
+    pub batch_header: MultiBatchHeader<Keccak256Hasher>,
+}
+
+/// Versioned envelope of the witness files.
+#[derive(Serialize, Deserialize)]
+struct WitnessFile<W> {
+    version: u32,
+    witness: W,
+}
+
+/// Version of the witness files, decoded before the witness itself.
+#[derive(Deserialize)]
+struct WitnessVersion {
+    version: u32,
+}
+
+impl Witness {
+    pub fn new(
+        initial_state: NetworkState,
+        batch_header: MultiBatchHeader<Keccak256Hasher>,
+    ) -> Self {
+        Self {
+            initial_state,
+            batch_header,
+        }
+    }
+
+    /// Encodes the witness in the given format.
+    pub fn to_bytes(&self, format: WitnessFormat) -> Result<Vec<u8>, WitnessError> {
+        let file = WitnessFile {
+            version: WITNESS_VERSION,
+            witness: self,
+        };
+
+        Ok(match format {
+            WitnessFormat::Binary => {
+                [WITNESS_MAGIC.as_slice(), &bincode::serialize(&file)?].concat()
+            }
+            WitnessFormat::Json => serde_json::to_vec_pretty(&file)?,
+        })
+    }
+
+    /// Decodes the witness, in the format given by its prefix.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WitnessError> {
+        match bytes.strip_prefix(WITNESS_MAGIC.as_slice()) {
+            Some(bytes) => {
+                check_version(bincode::deserialize(bytes)?)?;
+                Ok(bincode::deserialize::<WitnessFile<Self>>(bytes)?.witness)
+            }
+            None => {
+                check_version(serde_json::from_slice::<WitnessVersion>(bytes)?.version)?;
+                Ok(serde_json::from_slice::<WitnessFile<Self>>(bytes)?.witness)
+            }
+        }
+    }
+
+    /// Loads the witness file at the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WitnessError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Writes the witness to the given path, in the format given by its
+    /// extension.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), WitnessError> {
+        let path = path.as_ref();
+        Ok(std::fs::write(
+            path,
+            self.to_bytes(WitnessFormat::from_path(path))?,
+        )?)
+    }
+}
+
+fn check_version(version: u32) -> Result<(), WitnessError> {
+    if version != WITNESS_VERSION {
+        return Err(WitnessError::UnsupportedVersion(version));
+    }
+
+    Ok(())
+}
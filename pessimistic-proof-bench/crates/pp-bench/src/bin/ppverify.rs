@@ -0,0 +1,9 @@
+use pp_bench::{cli::unavailable, verify::main_with};
+use pp_prover::{MockProver, PessimisticProver, ProverBackend};
+
+fn main() -> anyhow::Result<()> {
+    main_with(&|backend| match backend {
+        ProverBackend::Mock => Ok(Box::new(MockProver::new()) as Box<dyn PessimisticProver>),
+        backend => Err(unavailable(backend)),
+    })
+}
@@ -0,0 +1,246 @@
+//! Command line of the `ppbench` binaries.
+//!
+//! The zkVM SDKs are built within their own workspaces, so each of them has
+//! its own `ppbench` binary, registering the provers it can build. Their
+//! results can be gathered in the same report with `--append`.
+//!
+//! With `--baseline`, the scenarios of a previous JSON report are executed
+//! again, and the binary fails if their cycles grew beyond `--threshold` or
+//! are no longer reported.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context};
+use clap::Parser;
+use pp_prover::{PessimisticProver, ProofMode, ProverBackend};
+
+use crate::{compare, run_scenario, scenario_matrix, BenchOptions, Report};
+
+/// The arguments for the benchmark driver.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+pub struct BenchArgs {
+    /// The backends to benchmark, comma separated. Also filters the scenarios
+    /// of the baseline.
+    #[clap(long, value_delimiter = ',', default_value = "mock")]
+    pub backends: Vec<ProverBackend>,
+
+    /// The proof modes to benchmark each backend with, comma separated. If
+    /// not set, the backends generate their default kind of proofs.
+    #[clap(long, value_delimiter = ',')]
+    pub proof_modes: Vec<ProofMode>,
+
+    /// The numbers of bridge exits, comma separated.
+    #[clap(long, value_delimiter = ',', default_value = "10")]
+    pub n_exits: Vec<usize>,
+
+    /// The numbers of imported bridge exits, comma separated.
+    #[clap(long, value_delimiter = ',', default_value = "10")]
+    pub n_imported_exits: Vec<usize>,
+
+    /// Only execute the guest, without proving.
+    #[clap(long)]
+    pub execute_only: bool,
+
+    /// The optional path to the custom sample data.
+    #[clap(long)]
+    pub sample_path: Option<PathBuf>,
+
+    /// The optional file to write the results in, as CSV if its extension is
+    /// `csv` and as JSON otherwise. If not set, the results are printed as
+    /// JSON.
+    #[clap(long)]
+    pub output: Option<PathBuf>,
+
+    /// Merge the results into the existing JSON output file.
+    #[clap(long, requires = "output")]
+    pub append: bool,
+
+    /// The optional JSON report to compare the cycles against. Its scenarios
+    /// are executed again, without proving, instead of the matrix.
+    #[clap(long)]
+    pub baseline: Option<PathBuf>,
+
+    /// The growth of the cycles over the baseline tolerated per scenario, in
+    /// percent.
+    #[clap(long, default_value = "1.0")]
+    pub threshold: f64,
+}
+
+/// Creates the prover of the given backend, if built in this binary.
+pub type ProverRegistry = dyn Fn(ProverBackend) -> anyhow::Result<Box<dyn PessimisticProver>>;
+
+/// Error of the registries for the backends which are not built in.
+pub fn unavailable(backend: ProverBackend) -> anyhow::Error {
+    anyhow::anyhow!("The {backend} backend is not available in this binary")
+}
+
+/// Runs the scenario matrix given by the arguments, or the scenarios of the
+/// given baseline. The prover of each backend and proof mode is created once,
+/// before running its scenarios.
+pub fn run(
+    args: &BenchArgs,
+    baseline: Option<&Report>,
+    registry: &ProverRegistry,
+) -> anyhow::Result<Report> {
+    let options = BenchOptions {
+        // The cycles are known from the execution only
+        execute_only: args.execute_only || baseline.is_some(),
+        sample_path: args.sample_path.clone(),
+    };
+
+    let scenarios = match baseline {
+        Some(baseline) => baseline
+            .results
+            .iter()
+            .map(|result| result.scenario)
+            .filter(|scenario| args.backends.contains(&scenario.backend))
+            .collect(),
+        None => scenario_matrix(
+            &args.backends,
+            &args.proof_modes,
+            &args.n_exits,
+            &args.n_imported_exits,
+        ),
+    };
+    if let Some(scenario) = scenarios
+        .iter()
+        .find(|scenario| scenario.n_exits > scenario.n_imported_exits)
+    {
+        bail!(
+            "The sample state cannot export {} bridge exit(s) from {} imported one(s)",
+            scenario.n_exits,
+            scenario.n_imported_exits
+        );
+    }
+
+    let mut provers = HashMap::new();
+    for scenario in &scenarios {
+        if let Entry::Vacant(entry) = provers.entry((scenario.backend, scenario.proof_mode)) {
+            let mut prover = registry(scenario.backend)?;
+            if let Some(proof_mode) = scenario.proof_mode {
+                prover.set_proof_mode(proof_mode)?;
+            }
+            entry.insert(prover);
+        }
+    }
+
+    let mut report = Report::default();
+    for scenario in scenarios {
+        eprintln!("Running {scenario:?}");
+        let prover = provers[&(scenario.backend, scenario.proof_mode)].as_ref();
+        let result = run_scenario(prover, scenario, &options)
+            .with_context(|| format!("{scenario:?} failed"))?;
+        eprintln!("{result:?}");
+        report.merge([result]);
+    }
+
+    Ok(report)
+}
+
+/// Entry point of the `ppbench` binaries.
+pub fn main_with(registry: &ProverRegistry) -> anyhow::Result<()> {
+    let args = BenchArgs::parse();
+    let baseline = args.baseline.as_ref().map(Report::load).transpose()?;
+    let results = run(&args, baseline.as_ref(), registry)?;
+
+    match &args.output {
+        Some(output) => {
+            let mut report = if args.append && output.exists() {
+                Report::load(output)?
+            } else {
+                Report::default()
+            };
+            report.merge(results.results.clone());
+            report.write(output)?;
+            eprintln!("Wrote {} result(s) to {output:?}", report.results.len());
+        }
+        None if baseline.is_none() => println!("{}", results.to_json()?),
+        None => {}
+    }
+
+    let Some(baseline) = baseline else {
+        return Ok(());
+    };
+
+    let deltas = compare(&baseline, &results);
+    for delta in &deltas {
+        println!("{delta}");
+    }
+
+    let regressions = deltas
+        .iter()
+        .filter(|delta| delta.is_regression(args.threshold))
+        .count();
+    if regressions > 0 {
+        bail!(
+            "The cycles of {regressions} scenario(s) grew by more than {}% or are no longer reported",
+            args.threshold
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pp_prover::MockProver;
+
+    use super::*;
+
+    fn registry(backend: ProverBackend) -> anyhow::Result<Box<dyn PessimisticProver>> {
+        match backend {
+            ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+            backend => Err(unavailable(backend)),
+        }
+    }
+
+    #[test]
+    fn run_matrix() {
+        let args = BenchArgs::parse_from([
+            "ppbench",
+            "--n-exits",
+            "1,2",
+            "--n-imported-exits",
+            "2",
+            "--execute-only",
+        ]);
+        let report = run(&args, None, &registry).unwrap();
+        assert_eq!(report.results.len(), 2);
+        assert!(report
+            .results
+            .iter()
+            .all(|result| result.execution_secs.is_some() && result.proving_secs.is_none()));
+
+        let args = BenchArgs::parse_from(["ppbench", "--backends", "mock,sp1"]);
+        assert!(run(&args, None, &registry).is_err());
+
+        let args = BenchArgs::parse_from(["ppbench", "--n-exits", "3", "--n-imported-exits", "2"]);
+        assert!(run(&args, None, &registry).is_err());
+
+        // The mock backend has a single kind of proofs
+        let args = BenchArgs::parse_from(["ppbench", "--proof-modes", "core,plonk"]);
+        assert!(run(&args, None, &registry).is_err());
+    }
+
+    #[test]
+    fn run_baseline_scenarios() {
+        let args = BenchArgs::parse_from(["ppbench", "--n-exits", "1", "--n-imported-exits", "2"]);
+        let mut baseline = run(&args, None, &registry).unwrap();
+        assert!(baseline.results[0].proving_secs.is_some());
+
+        // Only the scenarios of the baseline for the given backends are run
+        let mut other = baseline.results[0].clone();
+        other.scenario.backend = ProverBackend::Sp1;
+        baseline.merge([other]);
+
+        let args = BenchArgs::parse_from(["ppbench", "--n-exits", "5", "--n-imported-exits", "5"]);
+        let results = run(&args, Some(&baseline), &registry).unwrap();
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(results.results[0].scenario, baseline.results[0].scenario);
+        assert_eq!(results.results[0].proving_secs, None);
+    }
+}
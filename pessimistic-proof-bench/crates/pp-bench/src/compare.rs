@@ -0,0 +1,147 @@
+use std::fmt;
+
+use crate::{Report, Scenario};
+
+/// Cycles of one scenario against its baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CycleDelta {
+    pub scenario: Scenario,
+    pub baseline: Option<u64>,
+    pub current: Option<u64>,
+}
+
+impl CycleDelta {
+    /// Returns the growth of the cycles in percent, None if either count is
+    /// unavailable.
+    pub fn percent(&self) -> Option<f64> {
+        let (baseline, current) = (self.baseline?, self.current?);
+        if baseline == 0 {
+            return Some(if current == 0 { 0.0 } else { f64::INFINITY });
+        }
+
+        Some((current as f64 - baseline as f64) * 100.0 / baseline as f64)
+    }
+
+    /// Returns whether the cycles grew beyond the given threshold, in
+    /// percent. Losing the cycle count of a scenario which had one in the
+    /// baseline is a regression as well.
+    pub fn is_regression(&self, threshold_percent: f64) -> bool {
+        match (self.baseline, self.current) {
+            (Some(_), None) => true,
+            _ => self
+                .percent()
+                .is_some_and(|percent| percent > threshold_percent),
+        }
+    }
+}
+
+impl fmt::Display for CycleDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn count(cycles: Option<u64>) -> String {
+            cycles.map_or_else(|| "n/a".to_string(), |cycles| cycles.to_string())
+        }
+
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.scenario,
+            count(self.baseline),
+            count(self.current)
+        )?;
+        match self.percent() {
+            Some(percent) => write!(f, " ({percent:+.2}%)"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Compares the cycles of the current results against the baseline, in the
+/// order of the current results. The scenarios missing from the baseline have
+/// no baseline cycles.
+pub fn compare(baseline: &Report, current: &Report) -> Vec<CycleDelta> {
+    current
+        .results
+        .iter()
+        .map(|result| CycleDelta {
+            scenario: result.scenario,
+            baseline: baseline
+                .results
+                .iter()
+                .find(|previous| previous.scenario == result.scenario)
+                .and_then(|previous| previous.cycles),
+            current: result.cycles,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pp_prover::ProverBackend;
+
+    use super::*;
+    use crate::BenchResult;
+
+    fn report(cycles: &[(usize, Option<u64>)]) -> Report {
+        Report {
+            results: cycles
+                .iter()
+                .map(|&(n_exits, cycles)| BenchResult {
+                    scenario: Scenario {
+                        backend: ProverBackend::Sp1,
+                        proof_mode: None,
+                        n_exits,
+                        n_imported_exits: 100,
+                    },
+                    cycles,
+                    execution_secs: None,
+                    proving_secs: None,
+                    proof_size: None,
+                    verify_secs: None,
+                    peak_memory_kb: None,
+                    phase_cycles: Default::default(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn cycle_regressions() {
+        let baseline = report(&[
+            (1, Some(1000)),
+            (10, Some(2000)),
+            (50, None),
+            (200, Some(5000)),
+        ]);
+        let current = report(&[
+            (1, Some(1005)),
+            (10, Some(2200)),
+            (50, Some(3000)),
+            (100, Some(4000)),
+            (200, None),
+        ]);
+
+        let deltas = compare(&baseline, &current);
+        assert_eq!(deltas[0].percent(), Some(0.5));
+        assert_eq!(deltas[1].percent(), Some(10.0));
+        assert_eq!(deltas[2].percent(), None);
+        assert_eq!(deltas[3].baseline, None);
+
+        let regressions = deltas
+            .iter()
+            .filter(|delta| delta.is_regression(1.0))
+            .collect::<Vec<_>>();
+        assert_eq!(regressions, vec![&deltas[1], &deltas[4]]);
+        assert_eq!(
+            deltas[1].to_string(),
+            "sp1 n_exits=10 n_imported_exits=100: 2000 -> 2200 (+10.00%)"
+        );
+        assert_eq!(
+            deltas[2].to_string(),
+            "sp1 n_exits=50 n_imported_exits=100: n/a -> 3000"
+        );
+        assert_eq!(
+            deltas[4].to_string(),
+            "sp1 n_exits=200 n_imported_exits=100: 5000 -> n/a"
+        );
+    }
+}
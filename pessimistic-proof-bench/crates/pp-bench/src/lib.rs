@@ -0,0 +1,262 @@
+//! Benchmark harness running the pessimistic proof over a matrix of scenarios
+//! and zkVM backends, with machine-readable results.
+//!
+//! Every backend proves the same inputs, built from the sample data of
+//! `pessimistic-proof-test-suite`.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use pessimistic_proof_test_suite::sample_data::sample_inputs;
+use pp_prover::{PessimisticProver, Phase, ProofMode, ProverBackend, ProverError};
+use serde::{Deserialize, Serialize};
+
+pub mod cli;
+mod compare;
+mod report;
+pub mod verify;
+
+pub use compare::{compare, CycleDelta};
+pub use report::{Report, ReportError, ReportFormat};
+
+/// One point of the benchmark matrix.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct Scenario {
+    pub backend: ProverBackend,
+    /// The kind of the proofs, for the backends supporting several of them.
+    /// None for the default kind of the backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_mode: Option<ProofMode>,
+    pub n_exits: usize,
+    pub n_imported_exits: usize,
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.backend)?;
+        if let Some(proof_mode) = self.proof_mode {
+            write!(f, " {proof_mode}")?;
+        }
+
+        write!(
+            f,
+            " n_exits={} n_imported_exits={}",
+            self.n_exits, self.n_imported_exits
+        )
+    }
+}
+
+/// Returns every combination of the given backends, proof modes and numbers
+/// of bridge exits, grouped by backend and proof mode. Without any proof
+/// mode, the backends generate their default kind of proofs.
+pub fn scenario_matrix(
+    backends: &[ProverBackend],
+    proof_modes: &[ProofMode],
+    n_exits: &[usize],
+    n_imported_exits: &[usize],
+) -> Vec<Scenario> {
+    let proof_modes = match proof_modes {
+        [] => vec![None],
+        proof_modes => proof_modes.iter().copied().map(Some).collect(),
+    };
+
+    backends
+        .iter()
+        .flat_map(|&backend| {
+            proof_modes.iter().flat_map(move |&proof_mode| {
+                n_exits.iter().flat_map(move |&n_exits| {
+                    n_imported_exits
+                        .iter()
+                        .map(move |&n_imported_exits| Scenario {
+                            backend,
+                            proof_mode,
+                            n_exits,
+                            n_imported_exits,
+                        })
+                })
+            })
+        })
+        .collect()
+}
+
+/// Measures of one [`Scenario`]. The measures are None when the backend does
+/// not support the corresponding operation, or when it is skipped.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BenchResult {
+    #[serde(flatten)]
+    pub scenario: Scenario,
+    /// The number of cycles of the execution.
+    pub cycles: Option<u64>,
+    /// The duration of the execution, in seconds.
+    pub execution_secs: Option<f64>,
+    /// The duration of the proving, in seconds.
+    pub proving_secs: Option<f64>,
+    /// The size of the encoded proof, in bytes.
+    pub proof_size: Option<usize>,
+    /// The duration of the verification, in seconds.
+    pub verify_secs: Option<f64>,
+    /// The peak resident set size of the process during the scenario, in kB.
+    pub peak_memory_kb: Option<u64>,
+    /// The number of cycles spent in each [`Phase`] of the execution, if
+    /// reported by the guest program.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub phase_cycles: BTreeMap<Phase, u64>,
+}
+
+/// Options shared by all the scenarios.
+#[derive(Clone, Debug, Default)]
+pub struct BenchOptions {
+    /// Only execute the guest, without proving.
+    pub execute_only: bool,
+    /// The optional path to the custom sample data.
+    pub sample_path: Option<PathBuf>,
+}
+
+/// Runs the given scenario with the given prover.
+pub fn run_scenario(
+    prover: &dyn PessimisticProver,
+    scenario: Scenario,
+    options: &BenchOptions,
+) -> Result<BenchResult, ProverError> {
+    let inputs = sample_inputs(
+        scenario.n_exits,
+        scenario.n_imported_exits,
+        options.sample_path.clone(),
+    );
+    let (state, batch_header) = (&inputs.initial_state, &inputs.batch_header);
+
+    reset_peak_memory();
+
+    let execution = timed(|| prover.execute(state, batch_header))?;
+    let mut result = BenchResult {
+        scenario,
+        cycles: execution
+            .as_ref()
+            .and_then(|(execution, _)| execution.cycles),
+        execution_secs: execution.as_ref().map(|(_, elapsed)| elapsed.as_secs_f64()),
+        proving_secs: None,
+        proof_size: None,
+        verify_secs: None,
+        peak_memory_kb: None,
+        phase_cycles: execution
+            .map(|(execution, _)| execution.phase_cycles)
+            .unwrap_or_default(),
+    };
+
+    if !options.execute_only {
+        if let Some((proof, elapsed)) = timed(|| prover.prove(state, batch_header))? {
+            result.proving_secs = Some(elapsed.as_secs_f64());
+            result.proof_size = Some(proof.bytes.len());
+            result.verify_secs =
+                timed(|| prover.verify(&proof))?.map(|(_, elapsed)| elapsed.as_secs_f64());
+        }
+    }
+
+    result.peak_memory_kb = peak_memory_kb();
+
+    Ok(result)
+}
+
+/// Runs the given operation, and returns its result along with its duration.
+/// Returns None if the backend does not support it.
+fn timed<T>(
+    operation: impl FnOnce() -> Result<T, ProverError>,
+) -> Result<Option<(T, Duration)>, ProverError> {
+    let start = Instant::now();
+    match operation() {
+        Ok(value) => Ok(Some((value, start.elapsed()))),
+        Err(ProverError::Unsupported { .. }) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Resets the peak resident set size of the process. Only supported on Linux.
+fn reset_peak_memory() {
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Returns the peak resident set size of the process in kB, as reported by
+/// VmHWM. Only supported on Linux.
+fn peak_memory_kb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use pp_prover::MockProver;
+
+    use super::*;
+
+    #[test]
+    fn matrix_order() {
+        let matrix = scenario_matrix(
+            &[ProverBackend::Mock, ProverBackend::Sp1],
+            &[],
+            &[1, 10],
+            &[10],
+        );
+        assert_eq!(matrix.len(), 4);
+        assert_eq!(
+            matrix[1],
+            Scenario {
+                backend: ProverBackend::Mock,
+                proof_mode: None,
+                n_exits: 10,
+                n_imported_exits: 10,
+            }
+        );
+        assert_eq!(matrix[2].backend, ProverBackend::Sp1);
+
+        let matrix = scenario_matrix(
+            &[ProverBackend::Sp1],
+            &[ProofMode::Core, ProofMode::Plonk],
+            &[1, 10],
+            &[10],
+        );
+        assert_eq!(matrix.len(), 4);
+        assert_eq!(matrix[1].proof_mode, Some(ProofMode::Core));
+        assert_eq!(matrix[2].proof_mode, Some(ProofMode::Plonk));
+        assert_eq!(
+            matrix[2].to_string(),
+            "sp1 plonk n_exits=1 n_imported_exits=10"
+        );
+    }
+
+    #[test]
+    fn mock_scenario() {
+        let scenario = Scenario {
+            backend: ProverBackend::Mock,
+            proof_mode: None,
+            n_exits: 2,
+            n_imported_exits: 2,
+        };
+
+        let result = run_scenario(&MockProver::new(), scenario, &Default::default()).unwrap();
+        assert_eq!(result.scenario, scenario);
+        assert!(result.execution_secs.is_some());
+        assert!(result.proving_secs.is_some());
+        assert!(result.proof_size.unwrap() > 0);
+        assert!(result.verify_secs.is_some());
+
+        let options = BenchOptions {
+            execute_only: true,
+            ..Default::default()
+        };
+        let result = run_scenario(&MockProver::new(), scenario, &options).unwrap();
+        assert!(result.execution_secs.is_some());
+        assert_eq!(result.proving_secs, None);
+        assert_eq!(result.proof_size, None);
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use pp_prover::Phase;
+use serde::{Deserialize, Serialize};
+
+use crate::BenchResult;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    /// The report file cannot be read or written.
+    #[error("Report I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The report file cannot be decoded.
+    #[error("Report serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    /// Only the JSON reports can be loaded back.
+    #[error("Unable to load a {0:?} report")]
+    UnsupportedFormat(ReportFormat),
+}
+
+/// Format of the report files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    /// Returns the format of the given file, CSV if its extension is `csv`
+    /// and JSON otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => ReportFormat::Csv,
+            _ => ReportFormat::Json,
+        }
+    }
+}
+
+const CSV_HEADER: &str =
+    "backend,proof_mode,n_exits,n_imported_exits,cycles,execution_secs,proving_secs,\
+                          proof_size,verify_secs,peak_memory_kb";
+
+/// Returns the CSV header, with one `cycles_<phase>` column per [`Phase`].
+fn csv_header() -> String {
+    Phase::ALL
+        .iter()
+        .fold(CSV_HEADER.to_string(), |header, phase| {
+            format!("{header},cycles_{}", phase.name())
+        })
+}
+
+/// Results of a benchmark run, one per scenario.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Report {
+    pub results: Vec<BenchResult>,
+}
+
+impl Report {
+    /// Loads the JSON report at the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReportError> {
+        let path = path.as_ref();
+        match ReportFormat::from_path(path) {
+            ReportFormat::Json => Ok(serde_json::from_slice(&std::fs::read(path)?)?),
+            format => Err(ReportError::UnsupportedFormat(format)),
+        }
+    }
+
+    /// Adds the given results, replacing the previous results of the same
+    /// scenarios.
+    pub fn merge(&mut self, results: impl IntoIterator<Item = BenchResult>) {
+        for result in results {
+            match self
+                .results
+                .iter_mut()
+                .find(|previous| previous.scenario == result.scenario)
+            {
+                Some(previous) => *previous = result,
+                None => self.results.push(result),
+            }
+        }
+    }
+
+    /// Encodes the report as pretty JSON.
+    pub fn to_json(&self) -> Result<String, ReportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Encodes the report as CSV, one row per scenario. The unavailable
+    /// measures, including the cycles of the phases not reported by the
+    /// backend, are left empty.
+    pub fn to_csv(&self) -> String {
+        fn cell<T: ToString>(value: Option<T>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_default()
+        }
+
+        let mut csv = format!("{}\n", csv_header());
+        for result in &self.results {
+            let row = [
+                result.scenario.backend.to_string(),
+                cell(result.scenario.proof_mode),
+                result.scenario.n_exits.to_string(),
+                result.scenario.n_imported_exits.to_string(),
+                cell(result.cycles),
+                cell(result.execution_secs),
+                cell(result.proving_secs),
+                cell(result.proof_size),
+                cell(result.verify_secs),
+                cell(result.peak_memory_kb),
+            ];
+            let phases = Phase::ALL
+                .iter()
+                .map(|phase| cell(result.phase_cycles.get(phase)));
+            csv.push_str(&row.into_iter().chain(phases).collect::<Vec<_>>().join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// Writes the report to the given path, in the format given by its
+    /// extension.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), ReportError> {
+        let path = path.as_ref();
+        let contents = match ReportFormat::from_path(path) {
+            ReportFormat::Json => self.to_json()?,
+            ReportFormat::Csv => self.to_csv(),
+        };
+
+        Ok(std::fs::write(path, contents)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use pp_prover::{ProofMode, ProverBackend};
+
+    use super::*;
+    use crate::Scenario;
+
+    fn result(n_exits: usize, cycles: Option<u64>) -> BenchResult {
+        BenchResult {
+            scenario: Scenario {
+                backend: ProverBackend::Sp1,
+                proof_mode: None,
+                n_exits,
+                n_imported_exits: 10,
+            },
+            cycles,
+            execution_secs: Some(0.5),
+            proving_secs: None,
+            proof_size: None,
+            verify_secs: None,
+            peak_memory_kb: Some(1024),
+            phase_cycles: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn report_formats() {
+        let mut report = Report::default();
+        report.merge([result(1, Some(100)), result(10, None)]);
+        let mut updated = result(1, Some(200));
+        updated.phase_cycles =
+            BTreeMap::from([(Phase::PrevRootChecks, 20), (Phase::BalanceUpdates, 80)]);
+        report.merge([updated]);
+        assert_eq!(report.results.len(), 2);
+        let mut plonk = result(10, Some(300));
+        plonk.scenario.proof_mode = Some(ProofMode::Plonk);
+        report.merge([plonk]);
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.results[0].cycles, Some(200));
+
+        assert_eq!(
+            report.to_csv(),
+            format!(
+                "{}\nsp1,,1,10,200,0.5,,,,1024,,20,,,80,\nsp1,,10,10,,0.5,,,,1024,,,,,,\n\
+                 sp1,plonk,10,10,300,0.5,,,,1024,,,,,,\n",
+                csv_header()
+            )
+        );
+        assert!(csv_header().ends_with(",cycles_balance_updates,cycles_signature_recovery"));
+
+        let path = std::env::temp_dir().join(format!("ppbench-{}.json", std::process::id()));
+        report.write(&path).unwrap();
+        assert_eq!(Report::load(&path).unwrap(), report);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            Report::load(path.with_extension("csv")),
+            Err(ReportError::UnsupportedFormat(ReportFormat::Csv))
+        ));
+    }
+}
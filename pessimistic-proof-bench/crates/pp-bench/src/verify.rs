@@ -0,0 +1,142 @@
+//! Command line of the `ppverify` binaries.
+//!
+//! A proof saved by `ppgen --save-proof` is verified offline against the
+//! verification key written next to it, with the prover of the backend which
+//! generated it. The decoded output can be checked against an expected state
+//! transition, given as JSON.
+//!
+//! The Pico and Nexus provers cannot verify proofs nor return a verification
+//! key, so their proofs cannot be verified this way.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+use pp_prover::{verify_proof, ExpectedTransition, PessimisticProofOutput, Proof};
+
+use crate::cli::ProverRegistry;
+
+/// The arguments for the proof verifier.
+#[derive(Parser, Debug)]
+#[clap(
+    author,
+    version,
+    about,
+    long_about = None,
+    after_help = "Only the SP1, RISC Zero, OpenVM and mock proofs can be verified. The Pico and \
+                  Nexus provers don't support the offline verification."
+)]
+pub struct VerifyArgs {
+    /// The proof file to verify, as written by `ppgen --save-proof`.
+    pub proof: PathBuf,
+
+    /// The file of the hex encoded verification key. Defaults to the proof
+    /// file with the `vkey` extension.
+    #[clap(long)]
+    pub vkey: Option<PathBuf>,
+
+    /// The optional JSON file of the expected state transition. Its unset
+    /// fields are not checked.
+    #[clap(long)]
+    pub expected: Option<PathBuf>,
+}
+
+/// Verifies the proof given by the arguments, and returns its decoded output.
+pub fn verify(
+    args: &VerifyArgs,
+    registry: &ProverRegistry,
+) -> anyhow::Result<PessimisticProofOutput> {
+    let proof = Proof::load(&args.proof)
+        .with_context(|| format!("Failed to load the proof {:?}", args.proof))?;
+    let vkey_path = args
+        .vkey
+        .clone()
+        .unwrap_or_else(|| args.proof.with_extension("vkey"));
+    let vkey = std::fs::read_to_string(&vkey_path)
+        .with_context(|| format!("Failed to read the vkey {vkey_path:?}"))?;
+    let expected = args
+        .expected
+        .as_ref()
+        .map(|path| -> anyhow::Result<ExpectedTransition> {
+            let contents = std::fs::read(path)
+                .with_context(|| format!("Failed to read the expected transition {path:?}"))?;
+            Ok(serde_json::from_slice(&contents)?)
+        })
+        .transpose()?;
+
+    let prover = registry(proof.backend)?;
+    Ok(verify_proof(
+        prover.as_ref(),
+        &proof,
+        &vkey,
+        expected.as_ref(),
+    )?)
+}
+
+/// Entry point of the `ppverify` binaries.
+pub fn main_with(registry: &ProverRegistry) -> anyhow::Result<()> {
+    let args = VerifyArgs::parse();
+    let output = verify(&args, registry)?;
+    eprintln!("Verified the proof {:?}", args.proof);
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pessimistic_proof_test_suite::sample_data::sample_inputs;
+    use pp_prover::{
+        MockProver, PessimisticProver, ProofVerificationError, ProverBackend, ProverError,
+    };
+
+    use super::*;
+    use crate::cli::unavailable;
+
+    fn registry(backend: ProverBackend) -> anyhow::Result<Box<dyn PessimisticProver>> {
+        match backend {
+            ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+            backend => Err(unavailable(backend)),
+        }
+    }
+
+    #[test]
+    fn verify_saved_proof() {
+        let inputs = sample_inputs(1, 2, None);
+        let prover = MockProver::new();
+        let proof = prover
+            .prove(&inputs.initial_state, &inputs.batch_header)
+            .unwrap();
+        let output = prover.extract_output(&proof).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("ppverify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let proof_path = dir.join("proof.bin");
+        proof.write(&proof_path).unwrap();
+        std::fs::write(dir.join("proof.vkey"), prover.vkey().unwrap()).unwrap();
+        let mut expected = ExpectedTransition::from(&output);
+        let expected_path = dir.join("expected.json");
+        std::fs::write(&expected_path, serde_json::to_vec(&expected).unwrap()).unwrap();
+
+        let args = VerifyArgs::parse_from([
+            "ppverify".as_ref(),
+            proof_path.as_os_str(),
+            "--expected".as_ref(),
+            expected_path.as_os_str(),
+        ]);
+        let verified = verify(&args, &registry).unwrap();
+        assert_eq!(verified.new_pessimistic_root, output.new_pessimistic_root);
+
+        expected.prev_pessimistic_root = expected.new_pessimistic_root;
+        std::fs::write(&expected_path, serde_json::to_vec(&expected).unwrap()).unwrap();
+        let error = verify(&args, &registry).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(
+            error.downcast_ref::<ProverError>(),
+            Some(ProverError::Verification(
+                ProofVerificationError::InvalidPublicValues
+            ))
+        ));
+    }
+}
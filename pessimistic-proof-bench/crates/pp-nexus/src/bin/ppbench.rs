@@ -0,0 +1,11 @@
+use pp_bench::cli::{main_with, unavailable};
+use pp_nexus::NexusProver;
+use pp_prover::{MockProver, PessimisticProver, ProverBackend};
+
+fn main() -> anyhow::Result<()> {
+    main_with(&|backend| match backend {
+        ProverBackend::Nexus => Ok(Box::new(NexusProver::new()?) as Box<dyn PessimisticProver>),
+        ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+        backend => Err(unavailable(backend)),
+    })
+}
@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use nexus_sdk::{
+    compile::CompileOpts,
+    nova::seq::{Generate, Nova, PP},
+    Local, Parameters, Prover, Verifiable,
+};
+use pessimistic_proof_core::proof::Options;
+use pp_prover::{
+    Execution, KeyCache, KeyCacheError, MultiBatchHeader, NetworkState, PessimisticProofOutput,
+    PessimisticProver, Proof, ProofVerificationError, ProverBackend, ProverError,
+};
+
+/// The guest package compiled by the prover.
+pub const PACKAGE: &str = "pp-nexus-guest";
+
+/// Version of the Nexus SDK, which the public parameters depend on.
+const SDK_VERSION: &str = "0.2.4";
+
+/// Proves the pessimistic proof with the Nexus guest.
+///
+/// The Nova proofs cannot be exported, so they are verified right after being
+/// generated, and the proofs only carry their public values.
+pub struct NexusProver {
+    opts: CompileOpts,
+    pp: PP,
+}
+
+impl NexusProver {
+    /// Load the Nova public parameters from the default key cache.
+    pub fn new() -> anyhow::Result<Self> {
+        Self::with_key_cache(&KeyCache::default())
+    }
+
+    /// Load the Nova public parameters from the given key cache, or generate
+    /// and store them.
+    ///
+    /// The parameters aren't serializable with serde, so they are persisted
+    /// with the load and save of the SDK at the path of the key cache. They
+    /// don't depend on the guest, which is compiled for each run, so they are
+    /// keyed by its package and the version of the SDK.
+    pub fn with_key_cache(key_cache: &KeyCache) -> anyhow::Result<Self> {
+        let path = key_cache.path(
+            ProverBackend::Nexus,
+            PACKAGE.as_bytes(),
+            &format!("nova-seq-pp-{SDK_VERSION}"),
+        );
+
+        // The parameters which cannot be loaded are generated again
+        let pp = match path.exists().then(|| PP::load(&path)) {
+            Some(Ok(pp)) => pp,
+            _ => {
+                let pp = PP::generate()?;
+                Self::save(&pp, &path)?;
+                pp
+            }
+        };
+
+        Ok(Self {
+            opts: CompileOpts::new(PACKAGE),
+            pp,
+        })
+    }
+
+    /// Save the public parameters atomically, so that concurrent runs never
+    /// load partial parameters.
+    fn save(pp: &PP, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(KeyCacheError::from)?;
+        }
+
+        let partial = path.with_extension(format!("{}.partial", std::process::id()));
+        PP::save(pp, &partial).map_err(|error| anyhow!("{error:?}"))?;
+        std::fs::rename(partial, path).map_err(KeyCacheError::from)?;
+        Ok(())
+    }
+
+    /// Compile the guest program, consumed by each run.
+    fn compile(&self) -> anyhow::Result<Nova<Local>> {
+        Ok(Nova::compile(&self.opts)?)
+    }
+
+    fn unsupported(&self, operation: &'static str) -> ProverError {
+        ProverError::Unsupported {
+            backend: self.backend(),
+            operation,
+        }
+    }
+}
+
+impl PessimisticProver for NexusProver {
+    fn backend(&self) -> ProverBackend {
+        ProverBackend::Nexus
+    }
+
+    fn execute(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError> {
+        let view = self
+            .compile()
+            .map_err(ProverError::Execution)?
+            .run_with_input(&(state, batch_header))
+            .map_err(|error| ProverError::Execution(anyhow!("{error:?}")))?;
+        let output = view
+            .output::<PessimisticProofOutput>()
+            .map_err(|error| ProverError::OutputDecoding(format!("{error:?}")))?;
+
+        Ok(Execution {
+            output,
+            cycles: None,
+            phase_cycles: Default::default(),
+        })
+    }
+
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError> {
+        let proof = self
+            .compile()
+            .map_err(ProverError::Proving)?
+            .prove_with_input(&self.pp, &(state, batch_header))
+            .map_err(|error| ProverError::Proving(anyhow!("{error:?}")))?;
+        proof
+            .verify(&self.pp)
+            .map_err(|error| ProofVerificationError::Recursion(format!("{error:?}")))?;
+
+        let output = proof
+            .output::<PessimisticProofOutput>()
+            .map_err(|error| ProverError::OutputDecoding(format!("{error:?}")))?;
+
+        Ok(Proof {
+            backend: self.backend(),
+            bytes: Vec::new(),
+            public_values: PessimisticProofOutput::bincode_options()
+                .serialize(&output)
+                .map_err(|error| ProverError::Proving(error.into()))?,
+        })
+    }
+
+    fn verify(&self, _proof: &Proof) -> Result<(), ProverError> {
+        Err(self.unsupported("verify"))
+    }
+
+    fn vkey(&self) -> Result<String, ProverError> {
+        Err(self.unsupported("vkey"))
+    }
+
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError> {
+        proof.ensure_backend(self.backend())?;
+        pp_prover::decode_bincode_output(&proof.public_values)
+    }
+}
@@ -0,0 +1,11 @@
+use pp_bench::cli::{main_with, unavailable};
+use pp_openvm_host::OpenVmProver;
+use pp_prover::{MockProver, PessimisticProver, ProverBackend};
+
+fn main() -> anyhow::Result<()> {
+    main_with(&|backend| match backend {
+        ProverBackend::OpenVm => Ok(Box::new(OpenVmProver::new()?) as Box<dyn PessimisticProver>),
+        ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+        backend => Err(unavailable(backend)),
+    })
+}
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use bincode::Options;
+use openvm_circuit::arch::{instructions::exe::VmExe, ContinuationVmProof};
+use openvm_keccak256_circuit::Keccak256Rv32Config;
+use openvm_keccak256_transpiler::Keccak256TranspilerExtension;
+use openvm_native_compiler::conversion::CompilerOptions;
+use openvm_rv32im_transpiler::{
+    Rv32ITranspilerExtension, Rv32IoTranspilerExtension, Rv32MTranspilerExtension,
+};
+use openvm_sdk::{
+    config::{AppConfig, SdkVmConfig, DEFAULT_APP_LOG_BLOWUP, DEFAULT_LEAF_LOG_BLOWUP},
+    keygen::AppProvingKey,
+    prover::AppProver,
+    NonRootCommittedExe, Sdk, StdIn, F, SC,
+};
+use openvm_stark_sdk::config::FriParameters;
+use openvm_stark_sdk::openvm_stark_backend::p3_field::PrimeField32;
+use openvm_stark_sdk::p3_baby_bear::BabyBear;
+use openvm_transpiler::{
+    elf::Elf, openvm_platform::memory::MEM_SIZE, transpiler::Transpiler, FromElf,
+};
+use pp_prover::{
+    Execution, KeyCache, MultiBatchHeader, NetworkState, PessimisticProofOutput, PessimisticProver,
+    Proof, ProofVerificationError, ProverBackend, ProverError,
+};
+
+/// The ELF we want to execute inside the zkVM.
+pub const PESSIMISTIC_PROOF_ELF: &[u8] =
+    include_bytes!("../../pp-openvm-guest/target/riscv32im-risc0-zkvm-elf/release/pp-openvm-guest");
+
+/// Proves the pessimistic proof with the OpenVM guest.
+pub struct OpenVmProver {
+    sdk: Sdk,
+    exe: VmExe<F>,
+    app_pk: Arc<AppProvingKey<SdkVmConfig>>,
+    committed_exe: Arc<NonRootCommittedExe>,
+}
+
+impl OpenVmProver {
+    /// Transpile the guest and load its proving key from the default key
+    /// cache.
+    pub fn new() -> anyhow::Result<Self> {
+        Self::with_key_cache(&KeyCache::default())
+    }
+
+    /// Transpile the guest, and load its proving key from the given key
+    /// cache, or generate and store it.
+    pub fn with_key_cache(key_cache: &KeyCache) -> anyhow::Result<Self> {
+        let elf = Elf::decode(PESSIMISTIC_PROOF_ELF, MEM_SIZE as u32)?;
+
+        // Create transpiler with extensions
+        let transpiler = Transpiler::<BabyBear>::default()
+            .with_extension(Rv32ITranspilerExtension)
+            .with_extension(Rv32MTranspilerExtension)
+            .with_extension(Rv32IoTranspilerExtension)
+            .with_extension(Keccak256TranspilerExtension);
+        let exe = VmExe::from_elf(elf, transpiler)?;
+
+        // Configure VM
+        let mut config = Keccak256Rv32Config::default();
+        config.system = config.system.with_continuations();
+
+        let app_vm_config = SdkVmConfig::builder()
+            .system(openvm_sdk::config::SdkSystemConfig {
+                config: config.system,
+            })
+            .rv32i(Default::default())
+            .rv32m(Default::default())
+            .io(Default::default())
+            .keccak(Default::default())
+            .sha256(Default::default())
+            .build();
+
+        let app_config = AppConfig {
+            app_fri_params: FriParameters::standard_with_100_bits_conjectured_security(
+                DEFAULT_APP_LOG_BLOWUP,
+            )
+            .into(),
+            app_vm_config,
+            leaf_fri_params: FriParameters::standard_with_100_bits_conjectured_security(
+                DEFAULT_LEAF_LOG_BLOWUP,
+            )
+            .into(),
+            compiler_options: CompilerOptions {
+                enable_cycle_tracker: false,
+                ..Default::default()
+            },
+        };
+
+        // The proving key depends on the configuration, besides the ELF
+        let sdk = Sdk;
+        let name = format!(
+            "app-pk-{}",
+            KeyCache::digest(&bincode::serialize(&app_config)?)
+        );
+        let app_pk = Arc::new(key_cache.get_or_insert_with(
+            ProverBackend::OpenVm,
+            PESSIMISTIC_PROOF_ELF,
+            &name,
+            || sdk.app_keygen(app_config),
+        )?);
+        let committed_exe = sdk.commit_app_exe(app_pk.app_fri_params(), exe.clone())?;
+
+        Ok(Self {
+            sdk,
+            exe,
+            app_pk,
+            committed_exe,
+        })
+    }
+
+    /// Convert inputs to stdin, both bincode encoded back to back.
+    pub fn prepare_stdin(
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> anyhow::Result<StdIn> {
+        let mut input_data = bincode::DefaultOptions::new().serialize(state)?;
+        input_data.extend(bincode::DefaultOptions::new().serialize(batch_header)?);
+
+        Ok(StdIn::from_bytes(&input_data))
+    }
+
+    /// Convert the revealed field elements to the bytes revealed by the guest.
+    fn public_values_bytes(public_values: &[F]) -> Vec<u8> {
+        public_values
+            .iter()
+            .map(|value| value.as_canonical_u32() as u8)
+            .collect()
+    }
+
+    /// Decode the outputs revealed by the guest, padded up to the number of
+    /// public values.
+    fn decode_output(public_values: &[u8]) -> Result<PessimisticProofOutput, ProverError> {
+        bincode::DefaultOptions::new()
+            .allow_trailing_bytes()
+            .deserialize(public_values)
+            .map_err(|error| ProverError::OutputDecoding(error.to_string()))
+    }
+}
+
+impl PessimisticProver for OpenVmProver {
+    fn backend(&self) -> ProverBackend {
+        ProverBackend::OpenVm
+    }
+
+    fn execute(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError> {
+        let stdin = Self::prepare_stdin(state, batch_header).map_err(ProverError::Execution)?;
+        let public_values = self
+            .sdk
+            .execute(
+                self.exe.clone(),
+                self.app_pk.app_vm_pk.vm_config.clone(),
+                stdin,
+            )
+            .map_err(ProverError::Execution)?;
+
+        Ok(Execution {
+            output: Self::decode_output(&Self::public_values_bytes(&public_values))?,
+            cycles: None,
+            phase_cycles: Default::default(),
+        })
+    }
+
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError> {
+        let stdin = Self::prepare_stdin(state, batch_header).map_err(ProverError::Proving)?;
+        let app_prover = AppProver::new(self.app_pk.app_vm_pk.clone(), self.committed_exe.clone())
+            .with_program_name("pessimistic-proof");
+        let proof = app_prover.generate_app_proof(stdin);
+
+        Ok(Proof {
+            backend: self.backend(),
+            bytes: bincode::serialize(&proof)
+                .map_err(|error| ProverError::Proving(error.into()))?,
+            public_values: Self::public_values_bytes(&proof.user_public_values.public_values),
+        })
+    }
+
+    fn verify(&self, proof: &Proof) -> Result<(), ProverError> {
+        proof.ensure_backend(self.backend())?;
+        let app_proof: ContinuationVmProof<SC> = bincode::deserialize(&proof.bytes)
+            .map_err(|error| ProverError::ProofDecoding(error.to_string()))?;
+        if Self::public_values_bytes(&app_proof.user_public_values.public_values)
+            != proof.public_values
+        {
+            return Err(ProofVerificationError::InvalidPublicValues.into());
+        }
+
+        self.sdk
+            .verify_app_proof(&self.app_pk.get_app_vk(), &app_proof)
+            .map(|_| ())
+            .map_err(|error| ProofVerificationError::Core(error.to_string()).into())
+    }
+
+    fn vkey(&self) -> Result<String, ProverError> {
+        let commit: [F; 8] = self.committed_exe.get_program_commit().into();
+        Ok(hex::encode(
+            commit
+                .iter()
+                .flat_map(|value| value.as_canonical_u32().to_be_bytes())
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError> {
+        proof.ensure_backend(self.backend())?;
+        Self::decode_output(&proof.public_values)
+    }
+}
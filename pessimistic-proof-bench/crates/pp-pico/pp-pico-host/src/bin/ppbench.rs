@@ -0,0 +1,17 @@
+use pico_sdk::init_logger;
+use pp_bench::cli::{main_with, unavailable};
+use pp_pico_host::{PicoProver, PESSIMISTIC_PROOF_ELF_PATH};
+use pp_prover::{MockProver, PessimisticProver, ProverBackend};
+
+fn main() -> anyhow::Result<()> {
+    init_logger();
+
+    main_with(&|backend| match backend {
+        ProverBackend::Pico => Ok(
+            Box::new(PicoProver::from_path(PESSIMISTIC_PROOF_ELF_PATH)?)
+                as Box<dyn PessimisticProver>,
+        ),
+        ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+        backend => Err(unavailable(backend)),
+    })
+}
@@ -1,11 +1,14 @@
 use std::{path::PathBuf, time::Instant};
-use pico_sdk::{client::DefaultProverClient, init_logger};
-use agglayer_types::{Certificate, U256};
+use pico_sdk::init_logger;
+use agglayer_types::Certificate;
 use clap::Parser;
-use pessimistic_proof::bridge_exit::{NetworkId, TokenInfo};
+use pessimistic_proof::bridge_exit::NetworkId;
+use pessimistic_proof::witness::Witness;
 use pessimistic_proof::PessimisticProofOutput;
-use pessimistic_proof_core::{generate_pessimistic_proof, NetworkState};
+use pessimistic_proof_core::generate_pessimistic_proof;
 use pessimistic_proof_test_suite::sample_data::{self as data};
+use pp_pico_host::{PicoProver, PESSIMISTIC_PROOF_ELF_PATH};
+use pp_prover::PessimisticProver;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 use uuid::Uuid;
@@ -30,22 +33,11 @@ struct PPGenArgs {
     /// The optional path to the custom sample data.
     #[clap(long)]
     sample_path: Option<PathBuf>,
-}
 
-fn get_events(n: usize, path: Option<PathBuf>) -> Vec<(TokenInfo, U256)> {
-    if let Some(p) = path {
-        data::sample_bridge_exits(p)
-            .cycle()
-            .take(n)
-            .map(|e| (e.token_info, e.amount))
-            .collect::<Vec<_>>()
-    } else {
-        data::sample_bridge_exits_01()
-            .cycle()
-            .take(n)
-            .map(|e| (e.token_info, e.amount))
-            .collect::<Vec<_>>()
-    }
+    /// The optional witness file to prove instead of the sample data, in
+    /// binary or JSON.
+    #[clap(long)]
+    input: Option<PathBuf>,
 }
 
 // fn verify_proof(proof_output: &PessimisticProofOutput, public_values: &PublicValuesStruct) -> bool {
@@ -66,30 +58,23 @@ pub fn main() {
     let args = PPGenArgs::parse();
 
     // Load the ELF file
-    let elf = std::fs::read("pp-pico-guest/elf/riscv32im-pico-zkvm-elf").expect("Failed to load ELF file");
-    info!("Loaded ELF file, length: {}", elf.len());
-
-    let mut state = data::sample_state_00();
-    let old_state = state.state_b.clone();
-    let old_network_state = NetworkState::from(old_state.clone());
-
-    let bridge_exits = get_events(args.n_exits, args.sample_path.clone());
-    let imported_bridge_exits = get_events(args.n_imported_exits, args.sample_path);
+    let prover = PicoProver::from_path(PESSIMISTIC_PROOF_ELF_PATH).expect("Failed to load ELF file");
 
-    let certificate = state.apply_events(&imported_bridge_exits, &bridge_exits);
+    let witness = match &args.input {
+        Some(input) => Witness::load(input).expect("Failed to load the witness file"),
+        None => {
+            data::sample_inputs(args.n_exits, args.n_imported_exits, args.sample_path).witness()
+        }
+    };
+    let old_network_state = witness.initial_state;
+    let multi_batch_header = witness.batch_header;
 
     // info!(
     //     "Certificate {}: [{}]",
-    //     certificate.hash(),
-    //     serde_json::to_string(&certificate).unwrap()
+    //     inputs.certificate.hash(),
+    //     serde_json::to_string(&inputs.certificate).unwrap()
     // );
 
-    let l1_info_root = certificate.l1_info_root().unwrap().unwrap_or_default();
-    let multi_batch_header = old_state
-        .make_multi_batch_header(&certificate, state.get_signer(), l1_info_root)
-        .unwrap();
-
-
     // Validate inputs by running generate_pessimistic_proof first
     // let mut proof_output: PessimisticProofOutput = None;
     match generate_pessimistic_proof(old_network_state.clone(), &multi_batch_header) {
@@ -104,20 +89,14 @@ pub fn main() {
 
     info!(
         "Generating the proof for {} bridge exit(s) and {} imported bridge exit(s)",
-        bridge_exits.len(),
-        imported_bridge_exits.len()
+        multi_batch_header.bridge_exits.len(),
+        multi_batch_header.imported_bridge_exits.len()
     );
 
-    // Initialize the prover client
-    let client = DefaultProverClient::new(&elf);
-    let stdin_builder = client.get_stdin_builder();
-
-    // Write inputs to the VM
-    stdin_builder.borrow_mut().write(&old_network_state);
-    stdin_builder.borrow_mut().write(&multi_batch_header);
-
     let start = Instant::now();
-    let proof = client.prove_fast().expect("proving failed");
+    let proof = prover
+        .prove(&old_network_state, &multi_batch_header)
+        .expect("proving failed");
     let duration = start.elapsed();
     info!(
         "Successfully generated the proof with a latency of {:?}",
@@ -125,8 +104,7 @@ pub fn main() {
     );
 
     // // Decodes public values from the proof's public value stream.
-    // let public_buffer = proof.pv_stream.unwrap();
-    // let public_values = PublicValuesStruct::abi_decode(&public_buffer, true).unwrap();
+    // let public_values = prover.extract_output(&proof).unwrap();
 
     // verify_proof(&proof_output, &public_values);
 }
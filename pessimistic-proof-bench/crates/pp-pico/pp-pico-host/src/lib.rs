@@ -0,0 +1,87 @@
+use anyhow::anyhow;
+use pico_sdk::client::DefaultProverClient;
+use pp_prover::{
+    Execution, MultiBatchHeader, NetworkState, PessimisticProofOutput, PessimisticProver, Proof,
+    ProverBackend, ProverError,
+};
+
+/// The path of the guest ELF, relative to the pp-pico workspace.
+pub const PESSIMISTIC_PROOF_ELF_PATH: &str = "pp-pico-guest/elf/riscv32im-pico-zkvm-elf";
+
+/// Proves the pessimistic proof with the Pico guest.
+///
+/// Only the fast proofs are exposed by the Pico client, which can neither be
+/// exported nor verified on their own: the proofs only carry their public
+/// values.
+pub struct PicoProver {
+    elf: Vec<u8>,
+}
+
+impl PicoProver {
+    /// Create a new pessimistic proof prover from the guest ELF.
+    pub fn new(elf: Vec<u8>) -> Self {
+        Self { elf }
+    }
+
+    /// Create a new pessimistic proof prover from the ELF at the given path.
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self::new(std::fs::read(path)?))
+    }
+
+    fn unsupported(&self, operation: &'static str) -> ProverError {
+        ProverError::Unsupported {
+            backend: self.backend(),
+            operation,
+        }
+    }
+}
+
+impl PessimisticProver for PicoProver {
+    fn backend(&self) -> ProverBackend {
+        ProverBackend::Pico
+    }
+
+    fn execute(
+        &self,
+        _state: &NetworkState,
+        _batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError> {
+        Err(self.unsupported("execute"))
+    }
+
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError> {
+        // The stdin is bound to the client, hence one client per proof
+        let client = DefaultProverClient::new(&self.elf);
+        let stdin_builder = client.get_stdin_builder();
+        stdin_builder.borrow_mut().write(state);
+        stdin_builder.borrow_mut().write(batch_header);
+
+        let proof = client.prove_fast().map_err(ProverError::Proving)?;
+        let public_values = proof
+            .pv_stream
+            .ok_or_else(|| ProverError::Proving(anyhow!("missing public values")))?;
+
+        Ok(Proof {
+            backend: self.backend(),
+            bytes: Vec::new(),
+            public_values,
+        })
+    }
+
+    fn verify(&self, _proof: &Proof) -> Result<(), ProverError> {
+        Err(self.unsupported("verify"))
+    }
+
+    fn vkey(&self) -> Result<String, ProverError> {
+        Err(self.unsupported("vkey"))
+    }
+
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError> {
+        proof.ensure_backend(self.backend())?;
+        pp_prover::decode_bincode_output(&proof.public_values)
+    }
+}
@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+
+use agglayer_types::{Address, Certificate};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decode_bincode_output, OnchainProof, PessimisticProofOutput, PessimisticProver, ProverBackend,
+    ProverError,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FixtureError {
+    /// The fixture file cannot be read or written.
+    #[error("Fixture I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The fixture file cannot be encoded or decoded.
+    #[error("Fixture serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+    /// The proof or the public values are not valid hex.
+    #[error("Invalid hex encoding of the {field}: {source}")]
+    Hex {
+        field: &'static str,
+        #[source]
+        source: hex::FromHexError,
+    },
+    /// The decoded inputs do not match the public values of the proof.
+    #[error("The pessimistic proof inputs do not match the public values")]
+    InputsMismatch,
+    /// The fixture was generated for another verification key.
+    #[error("Fixture of another verification key. expected: {expected}, got: {got}")]
+    VkeyMismatch { expected: String, got: String },
+    #[error(transparent)]
+    Prover(#[from] ProverError),
+}
+
+/// Test vector of the on-chain verifier contracts, as written by the `ppgen`
+/// binaries with `--proof-dir`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProofFixture {
+    pub backend: ProverBackend,
+    /// The proven certificate. None if the inputs come from a witness file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<Certificate>,
+    /// The decoded public values.
+    pub pp_inputs: PessimisticProofOutput,
+    pub signer: Address,
+    /// The verification key of the guest program, hex encoded.
+    pub vkey: String,
+    /// The public values as given to the verifier contract, hex encoded.
+    pub public_values: String,
+    /// The proof as given to the verifier contract, hex encoded.
+    pub proof: String,
+}
+
+impl ProofFixture {
+    /// Builds the fixture of the given on-chain proof.
+    pub fn new(
+        proof: &OnchainProof,
+        vkey: String,
+        signer: Address,
+        certificate: Option<Certificate>,
+    ) -> Result<Self, ProverError> {
+        Ok(Self {
+            backend: proof.backend,
+            certificate,
+            pp_inputs: decode_bincode_output(&proof.public_values)?,
+            signer,
+            vkey,
+            public_values: format!("0x{}", hex::encode(&proof.public_values)),
+            proof: format!("0x{}", hex::encode(&proof.bytes)),
+        })
+    }
+
+    /// Decodes the on-chain proof of this fixture.
+    pub fn onchain_proof(&self) -> Result<OnchainProof, FixtureError> {
+        fn decode(field: &'static str, value: &str) -> Result<Vec<u8>, FixtureError> {
+            hex::decode(value.trim_start_matches("0x"))
+                .map_err(|source| FixtureError::Hex { field, source })
+        }
+
+        Ok(OnchainProof {
+            backend: self.backend,
+            bytes: decode("proof", &self.proof)?,
+            public_values: decode("public values", &self.public_values)?,
+        })
+    }
+
+    /// Returns the name of the fixture file, given by the backend, the
+    /// verification key and the new pessimistic root.
+    pub fn file_name(&self) -> String {
+        let vkey = self.vkey.trim_start_matches("0x");
+        let root = format!("{:x}", self.pp_inputs.new_pessimistic_root);
+        format!(
+            "{}-v{}-{}.json",
+            self.backend,
+            &vkey[..vkey.len().min(8)],
+            &root[..8]
+        )
+    }
+
+    /// Loads the fixture file at the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Writes the fixture as JSON in the given directory, and returns the
+    /// path of the file.
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<PathBuf, FixtureError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(self.file_name());
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(path)
+    }
+
+    /// Verifies the fixture locally with the given prover: the verification
+    /// key, the decoded inputs and the on-chain proof.
+    pub fn verify(&self, prover: &dyn PessimisticProver) -> Result<(), FixtureError> {
+        if self.backend != prover.backend() {
+            return Err(ProverError::BackendMismatch {
+                expected: prover.backend(),
+                got: self.backend,
+            }
+            .into());
+        }
+
+        let vkey = prover.vkey()?;
+        if !vkey.eq_ignore_ascii_case(&self.vkey) {
+            return Err(FixtureError::VkeyMismatch {
+                expected: vkey,
+                got: self.vkey.clone(),
+            });
+        }
+
+        let proof = self.onchain_proof()?;
+        let pp_inputs = PessimisticProofOutput::bincode_options()
+            .serialize(&self.pp_inputs)
+            .map_err(|error| ProverError::OutputDecoding(error.to_string()))?;
+        if pp_inputs != proof.public_values {
+            return Err(FixtureError::InputsMismatch);
+        }
+
+        Ok(prover.verify_onchain(&proof)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pessimistic_proof_test_suite::sample_data::sample_inputs;
+
+    use super::*;
+    use crate::MockProver;
+
+    #[test]
+    fn fixture_roundtrip() {
+        let inputs = sample_inputs(2, 2, None);
+        let prover = MockProver::new();
+        let proof = prover
+            .prove(&inputs.initial_state, &inputs.batch_header)
+            .unwrap();
+        let onchain = OnchainProof {
+            backend: proof.backend,
+            bytes: proof.bytes,
+            public_values: proof.public_values,
+        };
+
+        let fixture = ProofFixture::new(
+            &onchain,
+            prover.vkey().unwrap(),
+            inputs.signer,
+            Some(inputs.certificate),
+        )
+        .unwrap();
+
+        let dir = std::env::temp_dir().join(format!("pp-fixtures-{}", std::process::id()));
+        let path = fixture.write(&dir).unwrap();
+        assert!(path.ends_with(fixture.file_name()));
+        let loaded = ProofFixture::load(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.onchain_proof().unwrap(), onchain);
+        assert_eq!(loaded.signer, inputs.signer);
+        assert!(loaded.certificate.is_some());
+
+        // The mock proofs cannot be verified on-chain
+        assert!(matches!(
+            loaded.verify(&prover),
+            Err(FixtureError::Prover(ProverError::Unsupported { .. }))
+        ));
+
+        let mut tampered = loaded.clone();
+        tampered.pp_inputs.new_local_exit_root = Default::default();
+        assert!(matches!(
+            tampered.verify(&prover),
+            Err(FixtureError::InputsMismatch)
+        ));
+
+        tampered.vkey = "0x00".to_string();
+        assert!(matches!(
+            tampered.verify(&prover),
+            Err(FixtureError::VkeyMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+
+use pessimistic_proof_core::keccak::keccak256;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::ProverBackend;
+
+/// Environment variable overriding the directory of the key cache.
+pub const KEY_CACHE_DIR_ENV: &str = "PP_KEY_CACHE_DIR";
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyCacheError {
+    /// The cached keys cannot be read or written.
+    #[error("Key cache I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The keys cannot be encoded or decoded.
+    #[error("Key cache serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+}
+
+/// Directory persisting the proving and verifying keys across runs, so that
+/// the setup of each guest program is computed once.
+///
+/// The keys are stored under `<backend>/<elf hash>/<name>.bin`, the name
+/// distinguishing the keys of one ELF, e.g. by the version of the SDK.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl Default for KeyCache {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
+
+impl KeyCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Returns the directory given by [`KEY_CACHE_DIR_ENV`], or
+    /// `pessimistic-proof/keys` within the cache directory of the user.
+    pub fn default_dir() -> PathBuf {
+        if let Some(dir) = std::env::var_os(KEY_CACHE_DIR_ENV) {
+            return dir.into();
+        }
+
+        let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+        cache_dir.join("pessimistic-proof").join("keys")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the hex encoded hash of the given bytes, identifying an ELF
+    /// or a configuration in the cache.
+    pub fn digest(bytes: &[u8]) -> String {
+        format!("{:x}", keccak256(bytes))
+    }
+
+    /// Returns the path of the given keys of the given ELF.
+    pub fn path(&self, backend: ProverBackend, elf: &[u8], name: &str) -> PathBuf {
+        self.dir
+            .join(backend.as_str())
+            .join(Self::digest(elf))
+            .join(format!("{name}.bin"))
+    }
+
+    /// Loads the given keys of the given ELF, None if not cached.
+    pub fn load<K: DeserializeOwned>(
+        &self,
+        backend: ProverBackend,
+        elf: &[u8],
+        name: &str,
+    ) -> Result<Option<K>, KeyCacheError> {
+        match std::fs::read(self.path(backend, elf, name)) {
+            Ok(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Stores the given keys of the given ELF. The file is written
+    /// atomically, so that concurrent runs never load partial keys.
+    pub fn store<K: Serialize>(
+        &self,
+        backend: ProverBackend,
+        elf: &[u8],
+        name: &str,
+        keys: &K,
+    ) -> Result<(), KeyCacheError> {
+        let path = self.path(backend, elf, name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let partial = path.with_extension(format!("{}.partial", std::process::id()));
+        std::fs::write(&partial, bincode::serialize(keys)?)?;
+        Ok(std::fs::rename(partial, path)?)
+    }
+
+    /// Loads the given keys of the given ELF, or computes them with the given
+    /// setup and stores them. The keys which cannot be decoded, e.g. written
+    /// by another version, are computed again.
+    pub fn get_or_insert_with<K, E>(
+        &self,
+        backend: ProverBackend,
+        elf: &[u8],
+        name: &str,
+        setup: impl FnOnce() -> Result<K, E>,
+    ) -> Result<K, E>
+    where
+        K: Serialize + DeserializeOwned,
+        E: From<KeyCacheError>,
+    {
+        match self.load(backend, elf, name) {
+            Ok(Some(keys)) => return Ok(keys),
+            Ok(None) | Err(KeyCacheError::Serialization(_)) => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        let keys = setup()?;
+        self.store(backend, elf, name, &keys)?;
+
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProverError;
+
+    #[test]
+    fn keys_computed_once() {
+        let dir = std::env::temp_dir().join(format!("pp-key-cache-{}", std::process::id()));
+        let cache = KeyCache::new(&dir);
+        let (elf, other_elf) = (b"guest".as_slice(), b"other guest".as_slice());
+
+        let mut setups = 0;
+        let mut setup = |keys: (u64, Vec<u8>)| {
+            setups += 1;
+            Ok::<_, ProverError>(keys)
+        };
+
+        let keys = cache
+            .get_or_insert_with(ProverBackend::Sp1, elf, "keys", || setup((1, vec![2])))
+            .unwrap();
+        assert_eq!(keys, (1, vec![2]));
+
+        // Loaded by another process, without setup
+        let cache = KeyCache::new(&dir);
+        let keys = cache
+            .get_or_insert_with::<(u64, Vec<u8>), ProverError>(
+                ProverBackend::Sp1,
+                elf,
+                "keys",
+                || unreachable!(),
+            )
+            .unwrap();
+        assert_eq!(keys, (1, vec![2]));
+
+        // The keys of each ELF are cached separately
+        let keys = cache
+            .get_or_insert_with(ProverBackend::Sp1, other_elf, "keys", || {
+                setup((3, vec![4]))
+            })
+            .unwrap();
+        assert_eq!(keys, (3, vec![4]));
+        assert_ne!(
+            cache.path(ProverBackend::Sp1, elf, "keys"),
+            cache.path(ProverBackend::Sp1, other_elf, "keys")
+        );
+
+        // The keys which cannot be decoded are computed again
+        std::fs::write(cache.path(ProverBackend::Sp1, elf, "keys"), [0xff]).unwrap();
+        let keys = cache
+            .get_or_insert_with(ProverBackend::Sp1, elf, "keys", || setup((5, vec![6])))
+            .unwrap();
+        assert_eq!(keys, (5, vec![6]));
+        assert_eq!(
+            cache
+                .load::<(u64, Vec<u8>)>(ProverBackend::Sp1, elf, "keys")
+                .unwrap(),
+            Some((5, vec![6]))
+        );
+        assert_eq!(
+            cache
+                .load::<(u64, Vec<u8>)>(ProverBackend::Risc0, elf, "keys")
+                .unwrap(),
+            None
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(setups, 3);
+    }
+}
@@ -0,0 +1,413 @@
+//! Interface shared by the zkVM backends proving the pessimistic proof.
+//!
+//! Each backend crate implements [`PessimisticProver`] over its own SDK, so
+//! that the callers can pick the zkVM from their configuration through
+//! [`ProverBackend`]. The [`MockProver`] runs the pessimistic proof natively,
+//! for the tests which cannot afford to build the guests.
+
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+
+pub use agglayer_types::ProofVerificationError;
+use bincode::Options;
+use pessimistic_proof_core::local_exit_tree::hasher::Keccak256Hasher;
+pub use pessimistic_proof_core::{profiling::Phase, NetworkState, PessimisticProofOutput};
+use serde::{Deserialize, Serialize};
+
+mod fixture;
+mod key_cache;
+mod mock;
+mod verify;
+pub use fixture::{FixtureError, ProofFixture};
+pub use key_cache::{KeyCache, KeyCacheError, KEY_CACHE_DIR_ENV};
+pub use mock::MockProver;
+pub use verify::{verify_proof, ExpectedTransition};
+
+pub type Hasher = Keccak256Hasher;
+pub type MultiBatchHeader = pessimistic_proof_core::multi_batch_header::MultiBatchHeader<Hasher>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProverError {
+    /// The guest program failed to execute on the given inputs.
+    #[error("Execution failed: {0}")]
+    Execution(#[source] anyhow::Error),
+    /// The proof cannot be generated.
+    #[error("Proving failed: {0}")]
+    Proving(#[source] anyhow::Error),
+    /// The proof cannot be decoded by the backend.
+    #[error("Unable to decode the proof: {0}")]
+    ProofDecoding(String),
+    /// The proof is invalid.
+    #[error("Verification failed: {0}")]
+    Verification(#[from] ProofVerificationError),
+    /// The public values cannot be decoded as a [`PessimisticProofOutput`].
+    #[error("Unable to decode the proof output: {0}")]
+    OutputDecoding(String),
+    /// The proof was generated by another backend.
+    #[error("Proof of another backend. expected: {expected}, got: {got}")]
+    BackendMismatch {
+        expected: ProverBackend,
+        got: ProverBackend,
+    },
+    /// The keys of the guest program cannot be loaded from or stored in the
+    /// [`KeyCache`].
+    #[error(transparent)]
+    KeyCache(#[from] KeyCacheError),
+    /// The backend does not support the operation.
+    #[error("{operation} is not supported by {backend}")]
+    Unsupported {
+        backend: ProverBackend,
+        operation: &'static str,
+    },
+}
+
+/// The zkVM proving the pessimistic proof.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverBackend {
+    Sp1,
+    Risc0,
+    Pico,
+    OpenVm,
+    Nexus,
+    Valida,
+    /// Native execution, without any zkVM.
+    Mock,
+}
+
+impl ProverBackend {
+    pub const ALL: [ProverBackend; 7] = [
+        ProverBackend::Sp1,
+        ProverBackend::Risc0,
+        ProverBackend::Pico,
+        ProverBackend::OpenVm,
+        ProverBackend::Nexus,
+        ProverBackend::Valida,
+        ProverBackend::Mock,
+    ];
+
+    /// Returns the name of the backend, as used in the configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProverBackend::Sp1 => "sp1",
+            ProverBackend::Risc0 => "risc0",
+            ProverBackend::Pico => "pico",
+            ProverBackend::OpenVm => "openvm",
+            ProverBackend::Nexus => "nexus",
+            ProverBackend::Valida => "valida",
+            ProverBackend::Mock => "mock",
+        }
+    }
+}
+
+impl fmt::Display for ProverBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProverBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|backend| backend.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown prover backend: {s}"))
+    }
+}
+
+/// Kind of the proofs generated by the backends supporting several of them,
+/// from the fastest to generate to the cheapest to verify on-chain.
+#[derive(
+    Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofMode {
+    /// One proof per shard of the execution.
+    #[default]
+    Core,
+    /// The shard proofs recursively compressed into a single proof.
+    Compressed,
+    /// The compressed proof wrapped into a PLONK proof.
+    Plonk,
+    /// The compressed proof wrapped into a Groth16 proof.
+    Groth16,
+}
+
+impl ProofMode {
+    pub const ALL: [ProofMode; 4] = [
+        ProofMode::Core,
+        ProofMode::Compressed,
+        ProofMode::Plonk,
+        ProofMode::Groth16,
+    ];
+
+    /// Returns the name of the mode, as used in the configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofMode::Core => "core",
+            ProofMode::Compressed => "compressed",
+            ProofMode::Plonk => "plonk",
+            ProofMode::Groth16 => "groth16",
+        }
+    }
+}
+
+impl fmt::Display for ProofMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProofMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown proof mode: {s}"))
+    }
+}
+
+/// Proof generated by one backend, along with its public values.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Proof {
+    pub backend: ProverBackend,
+    /// The proof, encoded by the backend.
+    pub bytes: Vec<u8>,
+    /// The public values committed by the guest program.
+    pub public_values: Vec<u8>,
+}
+
+impl Proof {
+    /// Checks that this proof was generated by the given backend.
+    pub fn ensure_backend(&self, expected: ProverBackend) -> Result<(), ProverError> {
+        if self.backend != expected {
+            return Err(ProverError::BackendMismatch {
+                expected,
+                got: self.backend,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Loads the proof file at the given path, as written by
+    /// [`Proof::write`].
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        bincode::deserialize(&std::fs::read(path)?)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+    }
+
+    /// Writes the proof to the given path, encoded with bincode.
+    pub fn write(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let bytes = bincode::serialize(self)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        std::fs::write(path, bytes)
+    }
+}
+
+/// Proof verifiable by the on-chain verifier of one backend.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OnchainProof {
+    pub backend: ProverBackend,
+    /// The proof, encoded as expected by the verifier contract.
+    pub bytes: Vec<u8>,
+    /// The public values committed by the guest program, encoded with
+    /// [`PessimisticProofOutput::bincode_options`].
+    pub public_values: Vec<u8>,
+}
+
+/// Result of the execution of the guest program, without proving.
+#[derive(Clone, Debug)]
+pub struct Execution {
+    pub output: PessimisticProofOutput,
+    /// The number of cycles, if reported by the backend.
+    pub cycles: Option<u64>,
+    /// The number of cycles spent in each [`Phase`], if reported by the
+    /// guest program of the backend.
+    pub phase_cycles: BTreeMap<Phase, u64>,
+}
+
+/// Proves the pessimistic proof within one zkVM.
+pub trait PessimisticProver {
+    /// Returns the backend of this prover.
+    fn backend(&self) -> ProverBackend;
+
+    /// Executes the guest program on the given inputs, without proving.
+    fn execute(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError>;
+
+    /// Proves the guest program on the given inputs.
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError>;
+
+    /// Verifies the given proof against the verification key of this prover.
+    fn verify(&self, proof: &Proof) -> Result<(), ProverError>;
+
+    /// Returns the verification key of the guest program, hex encoded.
+    fn vkey(&self) -> Result<String, ProverError>;
+
+    /// Decodes the [`PessimisticProofOutput`] committed by the given proof.
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError>;
+
+    /// Sets the kind of the proofs generated by [`PessimisticProver::prove`],
+    /// for the backends supporting several of them.
+    fn set_proof_mode(&mut self, _mode: ProofMode) -> Result<(), ProverError> {
+        Err(ProverError::Unsupported {
+            backend: self.backend(),
+            operation: "set_proof_mode",
+        })
+    }
+
+    /// Proves the guest program on the given inputs, for the on-chain
+    /// verifier of this backend.
+    fn prove_onchain(
+        &self,
+        _state: &NetworkState,
+        _batch_header: &MultiBatchHeader,
+    ) -> Result<OnchainProof, ProverError> {
+        Err(ProverError::Unsupported {
+            backend: self.backend(),
+            operation: "prove_onchain",
+        })
+    }
+
+    /// Verifies the given on-chain proof against the verification key of
+    /// this prover, as the verifier contract does.
+    fn verify_onchain(&self, _proof: &OnchainProof) -> Result<(), ProverError> {
+        Err(ProverError::Unsupported {
+            backend: self.backend(),
+            operation: "verify_onchain",
+        })
+    }
+}
+
+/// Decodes the [`PessimisticProofOutput`] committed with
+/// [`PessimisticProofOutput::bincode_options`].
+pub fn decode_bincode_output(public_values: &[u8]) -> Result<PessimisticProofOutput, ProverError> {
+    PessimisticProofOutput::bincode_options()
+        .deserialize(public_values)
+        .map_err(|error| ProverError::OutputDecoding(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use pessimistic_proof_core::{
+        generate_pessimistic_proof, generate_pessimistic_proof_with_profiler, profiling::Profiler,
+    };
+    use pessimistic_proof_test_suite::sample_data::sample_inputs;
+
+    use super::*;
+
+    #[test]
+    fn backend_from_configuration() {
+        for backend in ProverBackend::ALL {
+            assert_eq!(backend.to_string().parse(), Ok(backend));
+            assert_eq!(
+                bincode::deserialize::<ProverBackend>(&bincode::serialize(&backend).unwrap())
+                    .unwrap(),
+                backend
+            );
+        }
+        assert_eq!("OpenVM".parse(), Ok(ProverBackend::OpenVm));
+        assert!("jolt".parse::<ProverBackend>().is_err());
+
+        for mode in ProofMode::ALL {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+            assert_eq!(serde_json::to_string(&mode).unwrap(), format!("\"{mode}\""));
+        }
+        assert!("stark".parse::<ProofMode>().is_err());
+
+        let proof = Proof {
+            backend: ProverBackend::Risc0,
+            bytes: Vec::new(),
+            public_values: Vec::new(),
+        };
+        assert!(matches!(
+            proof.ensure_backend(ProverBackend::Sp1),
+            Err(ProverError::BackendMismatch {
+                expected: ProverBackend::Sp1,
+                got: ProverBackend::Risc0,
+            })
+        ));
+    }
+
+    #[test]
+    fn bincode_output_roundtrip() {
+        let inputs = sample_inputs(2, 2, None);
+        let output =
+            generate_pessimistic_proof(inputs.initial_state, &inputs.batch_header).unwrap();
+
+        let public_values = PessimisticProofOutput::bincode_options()
+            .serialize(&output)
+            .unwrap();
+        let decoded = decode_bincode_output(&public_values).unwrap();
+        assert_eq!(decoded.new_local_exit_root, output.new_local_exit_root);
+        assert_eq!(decoded.new_pessimistic_root, output.new_pessimistic_root);
+
+        assert!(matches!(
+            decode_bincode_output(&public_values[1..]),
+            Err(ProverError::OutputDecoding(_))
+        ));
+    }
+
+    #[derive(Default)]
+    struct RecordingProfiler {
+        open: Option<Phase>,
+        occurrences: BTreeMap<Phase, usize>,
+    }
+
+    impl Profiler for RecordingProfiler {
+        fn start(&mut self, phase: Phase) {
+            assert_eq!(self.open.replace(phase), None);
+        }
+
+        fn end(&mut self, phase: Phase) {
+            assert_eq!(self.open.take(), Some(phase));
+            *self.occurrences.entry(phase).or_default() += 1;
+        }
+    }
+
+    #[test]
+    fn profiled_phases() {
+        let inputs = sample_inputs(2, 3, None);
+        let mut profiler = RecordingProfiler::default();
+        let output = generate_pessimistic_proof_with_profiler(
+            inputs.initial_state.clone(),
+            &inputs.batch_header,
+            &mut profiler,
+        )
+        .unwrap();
+        let expected =
+            generate_pessimistic_proof(inputs.initial_state, &inputs.batch_header).unwrap();
+        assert_eq!(output.new_pessimistic_root, expected.new_pessimistic_root);
+
+        assert_eq!(profiler.open, None);
+        assert_eq!(
+            profiler.occurrences,
+            BTreeMap::from([
+                (Phase::PrevRootChecks, 1),
+                (Phase::ImportedExitPaths, 3),
+                (Phase::NullifierUpdates, 3),
+                (
+                    Phase::BalanceUpdates,
+                    inputs.batch_header.balances_proofs.len()
+                ),
+                (Phase::SignatureRecovery, 1),
+            ])
+        );
+        assert_eq!(
+            Phase::from_name("balance_updates"),
+            Some(Phase::BalanceUpdates)
+        );
+        assert_eq!(Phase::from_name("balances"), None);
+    }
+}
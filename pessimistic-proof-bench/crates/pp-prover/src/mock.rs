@@ -0,0 +1,174 @@
+use anyhow::anyhow;
+use bincode::Options;
+use pessimistic_proof_core::{generate_pessimistic_proof, keccak::keccak256};
+
+use crate::{
+    Execution, MultiBatchHeader, NetworkState, PessimisticProofOutput, PessimisticProver, Proof,
+    ProofVerificationError, ProverBackend, ProverError,
+};
+
+/// Prefix of the mock proofs, so that they cannot be mistaken for real ones.
+const MOCK_PROOF_PREFIX: &[u8] = b"pessimistic-proof-mock";
+
+/// Runs the pessimistic proof natively, without any zkVM.
+///
+/// The mock proofs are deterministic and embed the inputs of the pessimistic
+/// proof, so that the verification can recompute the outputs. They only
+/// attest that the inputs are valid, and must never be accepted outside of
+/// tests.
+#[derive(Clone, Debug, Default)]
+pub struct MockProver;
+
+impl MockProver {
+    /// Create a new mock prover.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Run the pessimistic proof on the given inputs.
+    fn run(
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> anyhow::Result<PessimisticProofOutput> {
+        generate_pessimistic_proof(state.clone(), batch_header).map_err(|error| anyhow!("{error}"))
+    }
+
+    /// Encode the given output as committed by the zkVM guests.
+    fn encode_output(output: &PessimisticProofOutput) -> anyhow::Result<Vec<u8>> {
+        Ok(PessimisticProofOutput::bincode_options().serialize(output)?)
+    }
+}
+
+impl PessimisticProver for MockProver {
+    fn backend(&self) -> ProverBackend {
+        ProverBackend::Mock
+    }
+
+    fn execute(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError> {
+        Ok(Execution {
+            output: Self::run(state, batch_header).map_err(ProverError::Execution)?,
+            cycles: None,
+            phase_cycles: Default::default(),
+        })
+    }
+
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError> {
+        let output = Self::run(state, batch_header).map_err(ProverError::Proving)?;
+
+        let mut bytes = MOCK_PROOF_PREFIX.to_vec();
+        bincode::serialize_into(&mut bytes, &(state, batch_header))
+            .map_err(|error| ProverError::Proving(error.into()))?;
+
+        Ok(Proof {
+            backend: self.backend(),
+            bytes,
+            public_values: Self::encode_output(&output).map_err(ProverError::Proving)?,
+        })
+    }
+
+    fn verify(&self, proof: &Proof) -> Result<(), ProverError> {
+        proof.ensure_backend(self.backend())?;
+        let inputs = proof
+            .bytes
+            .strip_prefix(MOCK_PROOF_PREFIX)
+            .ok_or_else(|| ProverError::ProofDecoding("not a mock proof".to_string()))?;
+        let (state, batch_header): (NetworkState, MultiBatchHeader) = bincode::deserialize(inputs)
+            .map_err(|error| ProverError::ProofDecoding(error.to_string()))?;
+
+        let output = Self::run(&state, &batch_header)
+            .and_then(|output| Self::encode_output(&output))
+            .map_err(|error| ProofVerificationError::Core(error.to_string()))?;
+        if output != proof.public_values {
+            return Err(ProofVerificationError::InvalidPublicValues.into());
+        }
+
+        Ok(())
+    }
+
+    fn vkey(&self) -> Result<String, ProverError> {
+        Ok(keccak256(MOCK_PROOF_PREFIX).to_string())
+    }
+
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError> {
+        proof.ensure_backend(self.backend())?;
+        crate::decode_bincode_output(&proof.public_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pessimistic_proof_test_suite::sample_data::sample_inputs;
+
+    use super::*;
+
+    #[test]
+    fn mock_proof_roundtrip() {
+        let inputs = sample_inputs(3, 3, None);
+        let prover = MockProver::new();
+
+        let execution = prover
+            .execute(&inputs.initial_state, &inputs.batch_header)
+            .unwrap();
+        let proof = prover
+            .prove(&inputs.initial_state, &inputs.batch_header)
+            .unwrap();
+        assert_eq!(
+            proof,
+            prover
+                .prove(&inputs.initial_state, &inputs.batch_header)
+                .unwrap()
+        );
+        assert_eq!(prover.verify(&proof).map_err(|e| e.to_string()), Ok(()));
+
+        let output = prover.extract_output(&proof).unwrap();
+        assert_eq!(
+            output.new_pessimistic_root,
+            execution.output.new_pessimistic_root
+        );
+        assert_eq!(
+            output.new_local_exit_root,
+            inputs.certificate.new_local_exit_root
+        );
+
+        // The outputs are recomputed from the inputs
+        let mut tampered = proof.clone();
+        tampered.public_values[0] ^= 1;
+        assert!(matches!(
+            prover.verify(&tampered),
+            Err(ProverError::Verification(
+                ProofVerificationError::InvalidPublicValues
+            ))
+        ));
+
+        let mut other = proof;
+        other.backend = ProverBackend::Sp1;
+        assert!(matches!(
+            prover.verify(&other),
+            Err(ProverError::BackendMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn mock_rejects_invalid_inputs() {
+        let mut inputs = sample_inputs(1, 1, None);
+        inputs.batch_header.prev_local_exit_root = Default::default();
+
+        let prover = MockProver::new();
+        assert!(matches!(
+            prover.execute(&inputs.initial_state, &inputs.batch_header),
+            Err(ProverError::Execution(_))
+        ));
+        assert!(matches!(
+            prover.prove(&inputs.initial_state, &inputs.batch_header),
+            Err(ProverError::Proving(_))
+        ));
+    }
+}
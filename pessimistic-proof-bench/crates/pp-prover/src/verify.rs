@@ -0,0 +1,143 @@
+use agglayer_types::{Digest, ProofVerificationError};
+use pessimistic_proof_core::bridge_exit::NetworkId;
+use serde::{Deserialize, Serialize};
+
+use crate::{PessimisticProofOutput, PessimisticProver, Proof, ProverError};
+
+/// State transition expected from a proof. The unset fields are not checked.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExpectedTransition {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_network: Option<NetworkId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_info_root: Option<Digest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_local_exit_root: Option<Digest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_local_exit_root: Option<Digest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_pessimistic_root: Option<Digest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_pessimistic_root: Option<Digest>,
+}
+
+impl From<&PessimisticProofOutput> for ExpectedTransition {
+    fn from(output: &PessimisticProofOutput) -> Self {
+        Self {
+            origin_network: Some(output.origin_network),
+            l1_info_root: Some(output.l1_info_root),
+            prev_local_exit_root: Some(output.prev_local_exit_root),
+            new_local_exit_root: Some(output.new_local_exit_root),
+            prev_pessimistic_root: Some(output.prev_pessimistic_root),
+            new_pessimistic_root: Some(output.new_pessimistic_root),
+        }
+    }
+}
+
+impl ExpectedTransition {
+    /// Returns whether the given output matches every field set in this
+    /// transition.
+    pub fn matches(&self, output: &PessimisticProofOutput) -> bool {
+        fn check<T: PartialEq>(expected: &Option<T>, got: &T) -> bool {
+            expected.as_ref().map_or(true, |expected| expected == got)
+        }
+
+        check(&self.origin_network, &output.origin_network)
+            && check(&self.l1_info_root, &output.l1_info_root)
+            && check(&self.prev_local_exit_root, &output.prev_local_exit_root)
+            && check(&self.new_local_exit_root, &output.new_local_exit_root)
+            && check(&self.prev_pessimistic_root, &output.prev_pessimistic_root)
+            && check(&self.new_pessimistic_root, &output.new_pessimistic_root)
+    }
+}
+
+/// Verifies a saved proof offline with the given prover, and returns its
+/// decoded output.
+///
+/// The proof must have been generated for the given verification key, which
+/// must be the one of the prover, and its output must match the expected
+/// transition if any.
+pub fn verify_proof(
+    prover: &dyn PessimisticProver,
+    proof: &Proof,
+    vkey: &str,
+    expected: Option<&ExpectedTransition>,
+) -> Result<PessimisticProofOutput, ProverError> {
+    proof.ensure_backend(prover.backend())?;
+
+    let prover_vkey = prover.vkey()?;
+    if !prover_vkey.eq_ignore_ascii_case(vkey.trim()) {
+        return Err(ProofVerificationError::VkeyMismatch {
+            expected: prover_vkey,
+            got: vkey.trim().to_string(),
+        }
+        .into());
+    }
+
+    prover.verify(proof)?;
+
+    let output = prover.extract_output(proof)?;
+    if expected.is_some_and(|expected| !expected.matches(&output)) {
+        return Err(ProofVerificationError::InvalidPublicValues.into());
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use pessimistic_proof_test_suite::sample_data::sample_inputs;
+
+    use super::*;
+    use crate::MockProver;
+
+    #[test]
+    fn verify_saved_proof() {
+        let inputs = sample_inputs(2, 2, None);
+        let prover = MockProver::new();
+        let proof = prover
+            .prove(&inputs.initial_state, &inputs.batch_header)
+            .unwrap();
+        let vkey = prover.vkey().unwrap();
+
+        let path = std::env::temp_dir().join(format!("pp-proof-{}.bin", std::process::id()));
+        proof.write(&path).unwrap();
+        let loaded = Proof::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, proof);
+
+        let output = verify_proof(&prover, &loaded, &vkey, None).unwrap();
+        let mut expected = ExpectedTransition::from(&output);
+        assert!(verify_proof(&prover, &loaded, &format!(" {vkey}\n"), Some(&expected)).is_ok());
+        assert_eq!(
+            serde_json::from_str::<ExpectedTransition>(&serde_json::to_string(&expected).unwrap())
+                .unwrap(),
+            expected
+        );
+
+        expected.new_local_exit_root = Some(Digest::default());
+        assert!(matches!(
+            verify_proof(&prover, &loaded, &vkey, Some(&expected)),
+            Err(ProverError::Verification(
+                ProofVerificationError::InvalidPublicValues
+            ))
+        ));
+        assert!(ExpectedTransition {
+            origin_network: Some(output.origin_network),
+            ..Default::default()
+        }
+        .matches(&output));
+
+        assert!(matches!(
+            verify_proof(&prover, &loaded, " 0x00\n", None),
+            Err(ProverError::Verification(
+                ProofVerificationError::VkeyMismatch { expected, got }
+            )) if expected == vkey && got == "0x00"
+        ));
+
+        let mut tampered = loaded.clone();
+        tampered.bytes.truncate(4);
+        assert!(verify_proof(&prover, &tampered, &vkey, None).is_err());
+    }
+}
@@ -1,19 +1,61 @@
+use std::collections::BTreeMap;
+
 use bincode::Options;
 use pessimistic_proof_core::local_exit_tree::hasher::Keccak256Hasher;
 use pessimistic_proof_core::multi_batch_header::MultiBatchHeader;
-use pessimistic_proof_core::{generate_pessimistic_proof, NetworkState, PessimisticProofOutput};
+use pessimistic_proof_core::profiling::{NoopProfiler, Phase, Profiler};
+use pessimistic_proof_core::{
+    generate_pessimistic_proof_with_profiler, NetworkState, PessimisticProofOutput,
+};
 
 use risc0_zkvm::guest::env;
 
+/// Sums the cycles spent in each phase, from the cycle count of the guest.
+#[derive(Default)]
+struct CycleCounter {
+    started: u64,
+    totals: BTreeMap<Phase, u64>,
+}
+
+impl Profiler for CycleCounter {
+    fn start(&mut self, _phase: Phase) {
+        self.started = env::cycle_count();
+    }
+
+    fn end(&mut self, phase: Phase) {
+        *self.totals.entry(phase).or_default() += env::cycle_count() - self.started;
+    }
+}
+
 pub fn main() {
+    // The phases are only reported when the host asks for them, so that the
+    // proofs don't pay for the profiling
+    if env::read::<bool>() {
+        let mut profiler = CycleCounter::default();
+        run(&mut profiler);
+
+        // Report the cycles of each phase to the host, out of the journal
+        env::write(&profiler.totals.into_iter().collect::<Vec<_>>());
+    } else {
+        run(&mut NoopProfiler);
+    }
+}
+
+fn run(profiler: &mut impl Profiler) {
     // Read the input states from the host
-    let initial_state: NetworkState = env::read();
-    let batch_header: MultiBatchHeader<Keccak256Hasher> = env::read();
+    let (initial_state, batch_header) = profiler.measure(Phase::Deserialization, || {
+        let initial_state: NetworkState = env::read();
+        let batch_header: MultiBatchHeader<Keccak256Hasher> = env::read();
+        (initial_state, batch_header)
+    });
 
     // Generate the proof
-    let outputs = generate_pessimistic_proof(initial_state, &batch_header)
+    let outputs = generate_pessimistic_proof_with_profiler(initial_state, &batch_header, profiler)
         .expect("Failed to generate pessimistic proof");
 
-    // Commit the outputs to the journal
-    env::commit(&outputs);
-}
\ No newline at end of file
+    // Commit the outputs to the journal, encoded as for the verifier contract
+    let pp_inputs = PessimisticProofOutput::bincode_options()
+        .serialize(&outputs)
+        .unwrap();
+    env::commit_slice(&pp_inputs);
+}
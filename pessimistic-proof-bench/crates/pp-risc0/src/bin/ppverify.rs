@@ -0,0 +1,15 @@
+use pp_bench::{cli::unavailable, verify::main_with};
+use pp_prover::{MockProver, PessimisticProver, ProverBackend};
+use pp_risc0::Risc0Prover;
+
+fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    main_with(&|backend| match backend {
+        ProverBackend::Risc0 => Ok(Box::new(Risc0Prover::new()) as Box<dyn PessimisticProver>),
+        ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+        backend => Err(unavailable(backend)),
+    })
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use pp_prover::ProofFixture;
+use pp_risc0::Risc0Prover;
+use tracing::info;
+
+/// The arguments for the fixture verifier.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct VerifyFixtureArgs {
+    /// The fixture file to verify, as written by `ppgen --proof-dir`.
+    fixture: PathBuf,
+}
+
+pub fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+
+    let args = VerifyFixtureArgs::parse();
+    let fixture = ProofFixture::load(&args.fixture)?;
+    fixture.verify(&Risc0Prover::new())?;
+    info!(
+        "Verified the fixture {:?}: {:?}",
+        args.fixture, fixture.pp_inputs
+    );
+
+    Ok(())
+}
@@ -1 +1,196 @@
-pub use methods;
\ No newline at end of file
+pub use methods;
+
+use std::collections::BTreeMap;
+
+use methods::{PP_RISC0_GUEST_ELF, PP_RISC0_GUEST_ID};
+use pp_prover::{
+    Execution, MultiBatchHeader, NetworkState, OnchainProof, PessimisticProofOutput,
+    PessimisticProver, Phase, Proof, ProofVerificationError, ProverBackend, ProverError,
+};
+use risc0_zkvm::{
+    default_executor, default_prover,
+    sha::{Digest, Digestible},
+    ExecutorEnv, ExecutorEnvBuilder, Groth16Receipt, Groth16ReceiptVerifierParameters,
+    InnerReceipt, ProverOpts, Receipt, ReceiptClaim,
+};
+
+/// Length of the selector prefixing the seals, identifying the verifier
+/// parameters to the verifier router contract.
+const SELECTOR_LEN: usize = 4;
+
+/// Proves the pessimistic proof with the RISC Zero guest.
+#[derive(Clone, Debug, Default)]
+pub struct Risc0Prover;
+
+impl Risc0Prover {
+    /// Create a new pessimistic proof prover.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Convert inputs to the executor environment, without profiling.
+    pub fn prepare_env(
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> anyhow::Result<ExecutorEnv<'static>> {
+        let mut builder = ExecutorEnv::builder();
+        Self::write_inputs(&mut builder, state, batch_header, false)?;
+        builder.build()
+    }
+
+    /// Write the inputs to the given executor environment. If `profile` is
+    /// set, the guest writes the cycles of each phase to its stdout.
+    fn write_inputs(
+        builder: &mut ExecutorEnvBuilder<'_>,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+        profile: bool,
+    ) -> anyhow::Result<()> {
+        builder.write(&profile)?.write(state)?.write(batch_header)?;
+        Ok(())
+    }
+
+    /// Decode the cycles of each phase, written by the guest to its stdout.
+    fn decode_phase_cycles(stdout: &[u8]) -> Result<BTreeMap<Phase, u64>, ProverError> {
+        let phase_cycles: Vec<(Phase, u64)> = risc0_zkvm::serde::from_slice(stdout)
+            .map_err(|error| ProverError::OutputDecoding(error.to_string()))?;
+        Ok(phase_cycles.into_iter().collect())
+    }
+
+    /// Decode the outputs committed to the journal.
+    fn decode_output(journal: &[u8]) -> Result<PessimisticProofOutput, ProverError> {
+        pp_prover::decode_bincode_output(journal)
+    }
+}
+
+impl PessimisticProver for Risc0Prover {
+    fn backend(&self) -> ProverBackend {
+        ProverBackend::Risc0
+    }
+
+    fn execute(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError> {
+        let mut stdout = Vec::new();
+        let mut builder = ExecutorEnv::builder();
+        Self::write_inputs(&mut builder, state, batch_header, true)
+            .map_err(ProverError::Execution)?;
+        let env = builder
+            .stdout(&mut stdout)
+            .build()
+            .map_err(ProverError::Execution)?;
+        let session = default_executor()
+            .execute(env, PP_RISC0_GUEST_ELF)
+            .map_err(ProverError::Execution)?;
+
+        Ok(Execution {
+            output: Self::decode_output(&session.journal.bytes)?,
+            cycles: Some(session.cycles()),
+            phase_cycles: Self::decode_phase_cycles(&stdout)?,
+        })
+    }
+
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError> {
+        let env = Self::prepare_env(state, batch_header).map_err(ProverError::Proving)?;
+        let receipt = default_prover()
+            .prove(env, PP_RISC0_GUEST_ELF)
+            .map_err(ProverError::Proving)?
+            .receipt;
+
+        Ok(Proof {
+            backend: self.backend(),
+            bytes: bincode::serialize(&receipt)
+                .map_err(|error| ProverError::Proving(error.into()))?,
+            public_values: receipt.journal.bytes.clone(),
+        })
+    }
+
+    fn verify(&self, proof: &Proof) -> Result<(), ProverError> {
+        proof.ensure_backend(self.backend())?;
+        let receipt: Receipt = bincode::deserialize(&proof.bytes)
+            .map_err(|error| ProverError::ProofDecoding(error.to_string()))?;
+        if receipt.journal.bytes != proof.public_values {
+            return Err(ProofVerificationError::InvalidPublicValues.into());
+        }
+
+        receipt.verify(PP_RISC0_GUEST_ID).map_err(|error| {
+            let error = error.to_string();
+            match receipt.inner {
+                InnerReceipt::Groth16(_) => ProofVerificationError::Groth16(error),
+                InnerReceipt::Succinct(_) => ProofVerificationError::Recursion(error),
+                _ => ProofVerificationError::Core(error),
+            }
+            .into()
+        })
+    }
+
+    fn vkey(&self) -> Result<String, ProverError> {
+        Ok(hex::encode(Digest::from(PP_RISC0_GUEST_ID).as_bytes()))
+    }
+
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError> {
+        proof.ensure_backend(self.backend())?;
+        Self::decode_output(&proof.public_values)
+    }
+
+    fn prove_onchain(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<OnchainProof, ProverError> {
+        let env = Self::prepare_env(state, batch_header).map_err(ProverError::Proving)?;
+        let receipt = default_prover()
+            .prove_with_opts(env, PP_RISC0_GUEST_ELF, &ProverOpts::groth16())
+            .map_err(ProverError::Proving)?
+            .receipt;
+        let groth16 = receipt
+            .inner
+            .groth16()
+            .map_err(|error| ProverError::Proving(error.into()))?;
+
+        Ok(OnchainProof {
+            backend: self.backend(),
+            bytes: [
+                &groth16.verifier_parameters.as_bytes()[..SELECTOR_LEN],
+                groth16.seal.as_slice(),
+            ]
+            .concat(),
+            public_values: receipt.journal.bytes.clone(),
+        })
+    }
+
+    fn verify_onchain(&self, proof: &OnchainProof) -> Result<(), ProverError> {
+        if proof.backend != self.backend() {
+            return Err(ProverError::BackendMismatch {
+                expected: self.backend(),
+                got: proof.backend,
+            });
+        }
+
+        let verifier_parameters = Groth16ReceiptVerifierParameters::default().digest();
+        let seal = proof
+            .bytes
+            .strip_prefix(&verifier_parameters.as_bytes()[..SELECTOR_LEN])
+            .ok_or_else(|| ProverError::ProofDecoding("unknown seal selector".to_string()))?;
+
+        let claim = ReceiptClaim::ok(PP_RISC0_GUEST_ID, proof.public_values.clone());
+        let receipt = Receipt::new(
+            InnerReceipt::Groth16(Groth16Receipt::new(
+                seal.to_vec(),
+                claim.into(),
+                verifier_parameters,
+            )),
+            proof.public_values.clone(),
+        );
+
+        receipt
+            .verify(PP_RISC0_GUEST_ID)
+            .map_err(|error| ProofVerificationError::Groth16(error.to_string()).into())
+    }
+}
@@ -1,18 +1,17 @@
 use std::{path::PathBuf, time::Instant};
 use clap::Parser;
-use methods::{
-    PP_RISC0_GUEST_ELF, PP_RISC0_GUEST_ID
-};
-use risc0_zkvm::{default_prover, ExecutorEnv};
+use pp_prover::{PessimisticProver, ProofFixture};
+use pp_risc0::Risc0Prover;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 use uuid::Uuid;
 use tracing_subscriber;
 
-use agglayer_types::{Certificate, U256};
-use pessimistic_proof::bridge_exit::{NetworkId, TokenInfo};
+use agglayer_types::Certificate;
+use pessimistic_proof::bridge_exit::NetworkId;
+use pessimistic_proof::witness::Witness;
 use pessimistic_proof::PessimisticProofOutput;
-use pessimistic_proof_core::{generate_pessimistic_proof, NetworkState};
+use pessimistic_proof_core::generate_pessimistic_proof;
 use pessimistic_proof_test_suite::sample_data::{self as data};
 
 /// The arguments for the proof generator
@@ -27,29 +26,21 @@ struct ProofGenArgs {
     #[clap(long, default_value = "10")]
     n_imported_exits: usize,
 
-    /// Optional output directory for proof JSON files
+    /// Optional output directory for the fixture JSON files of groth16 proofs, for the verifier contract
     #[clap(long)]
     proof_dir: Option<PathBuf>,
 
+    /// Optional file to save the proof in, to verify it later with `ppverify`. The vkey is written next to it, with the `vkey` extension
+    #[clap(long, conflicts_with = "proof_dir")]
+    save_proof: Option<PathBuf>,
+
     /// Optional path to custom sample data
     #[clap(long)]
     sample_path: Option<PathBuf>,
-}
 
-fn get_events(n: usize, path: Option<PathBuf>) -> Vec<(TokenInfo, U256)> {
-    if let Some(p) = path {
-        data::sample_bridge_exits(p)
-            .cycle()
-            .take(n)
-            .map(|e| (e.token_info, e.amount))
-            .collect::<Vec<_>>()
-    } else {
-        data::sample_bridge_exits_01()
-            .cycle()
-            .take(n)
-            .map(|e| (e.token_info, e.amount))
-            .collect::<Vec<_>>()
-    }
+    /// Optional witness file to prove instead of the sample data, in binary or JSON
+    #[clap(long)]
+    input: Option<PathBuf>,
 }
 
 // fn verify_proof(proof_output: &PessimisticProofOutput, public_values: &PessimisticProofOutput) -> bool {
@@ -71,30 +62,26 @@ pub fn main() {
 
     let args = ProofGenArgs::parse();
 
-    // Initialize state
-    let mut state = data::sample_state_00();
-    let old_state = state.state_b.clone();
-    let old_network_state = NetworkState::from(old_state.clone());
-
-    // Get bridge exits
-    let bridge_exits = get_events(args.n_exits, args.sample_path.clone());
-    let imported_bridge_exits = get_events(args.n_imported_exits, args.sample_path);
-
-    // Apply events and get certificate
-    let certificate = state.apply_events(&imported_bridge_exits, &bridge_exits);
+    // Prepare the initial state and the batch header
+    let (witness, certificate) = match &args.input {
+        Some(input) => (
+            Witness::load(input).expect("Failed to load the witness file"),
+            None,
+        ),
+        None => {
+            let inputs = data::sample_inputs(args.n_exits, args.n_imported_exits, args.sample_path);
+            (inputs.witness(), Some(inputs.certificate))
+        }
+    };
+    let old_network_state = witness.initial_state;
+    let multi_batch_header = witness.batch_header;
 
     // info!(
     //     "Certificate {}: [{}]",
-    //     certificate.hash(),
-    //     serde_json::to_string(&certificate).unwrap()
+    //     inputs.certificate.hash(),
+    //     serde_json::to_string(&inputs.certificate).unwrap()
     // );
 
-    // Prepare batch header
-    let l1_info_root = certificate.l1_info_root().unwrap().unwrap_or_default();
-    let multi_batch_header = old_state
-        .make_multi_batch_header(&certificate, state.get_signer(), l1_info_root)
-        .unwrap();
-
     // Validate inputs by running generate_pessimistic_proof first
     // let mut proof_output: PessimisticProofOutput = None;
     match generate_pessimistic_proof(old_network_state.clone(), &multi_batch_header) {
@@ -109,59 +96,53 @@ pub fn main() {
 
     info!(
         "Generating proof for {} bridge exit(s) and {} imported bridge exit(s)",
-        bridge_exits.len(),
-        imported_bridge_exits.len()
+        multi_batch_header.bridge_exits.len(),
+        multi_batch_header.imported_bridge_exits.len()
     );
 
-    // Create executor environment
-    let env = ExecutorEnv::builder()
-        .write(&old_network_state)
-        .unwrap()
-        .write(&multi_batch_header)
-        .unwrap()
-        .build()
-        .unwrap();
-
     // Generate proof
+    let prover = Risc0Prover::new();
     let start = Instant::now();
-    let prove_info = default_prover()
-        .prove(env, PP_RISC0_GUEST_ELF)
+
+    // Handle proof output directory if specified
+    if let Some(proof_dir) = args.proof_dir {
+        let proof = prover
+            .prove_onchain(&old_network_state, &multi_batch_header)
+            .expect("proving failed");
+        info!(
+            "Successfully generated groth16 proof with latency of {:?}",
+            start.elapsed()
+        );
+
+        let vkey = prover.vkey().expect("vkey");
+        let fixture = ProofFixture::new(&proof, vkey, multi_batch_header.signer, certificate)
+            .expect("invalid proof output");
+        prover.verify_onchain(&proof).unwrap();
+
+        let fixture_path = fixture.write(&proof_dir).expect("failed to write fixture");
+        info!("Writing fixture to {:?}", fixture_path);
+        return;
+    }
+
+    let proof = prover
+        .prove(&old_network_state, &multi_batch_header)
         .expect("proving failed");
     let duration = start.elapsed();
-    
+
     info!(
         "Successfully generated proof with latency of {:?}",
         duration
     );
 
-    let receipt = prove_info.receipt;
-    
-    // Verify the receipt
-    receipt.verify(PP_RISC0_GUEST_ID).unwrap();
-
-    // // Get the proof output
-    // let output: PessimisticProofOutput = receipt.journal.decode().unwrap();
-
-    // verify_proof(&proof_output, &public_values);
-
-    // // Handle proof output directory if specified
-    // if let Some(proof_dir) = args.proof_dir {
-    //     let proof_path = proof_dir.join(format!(
-    //         "{}-exits-{}.json",
-    //         args.n_exits,
-    //         Uuid::new_v4()
-    //     ));
-        
-    //     if let Err(e) = std::fs::create_dir_all(&proof_dir) {
-    //         warn!("Failed to create directory: {e}");
-    //     }
-        
-    //     info!("Writing proof to {:?}", proof_path);
-    //     std::fs::write(
-    //         proof_path,
-    //         serde_json::to_string_pretty(&output).unwrap()
-    //     ).expect("failed to write proof");
-    // } else {
-    //     info!("Proof output: {:?}", output);
-    // }
-}
\ No newline at end of file
+    prover.verify(&proof).unwrap();
+
+    let output: PessimisticProofOutput = prover.extract_output(&proof).unwrap();
+    info!("Proof output: {:?}", output);
+
+    if let Some(path) = args.save_proof {
+        proof.write(&path).expect("failed to write the proof");
+        std::fs::write(path.with_extension("vkey"), prover.vkey().expect("vkey"))
+            .expect("failed to write the vkey");
+        info!("Saved the proof to {:?}", path);
+    }
+}
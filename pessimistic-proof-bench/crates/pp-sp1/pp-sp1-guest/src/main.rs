@@ -3,14 +3,46 @@
 use bincode::Options;
 use pessimistic_proof_core::local_exit_tree::hasher::Keccak256Hasher;
 use pessimistic_proof_core::multi_batch_header::MultiBatchHeader;
-use pessimistic_proof_core::{generate_pessimistic_proof, NetworkState, PessimisticProofOutput};
+use pessimistic_proof_core::profiling::{NoopProfiler, Phase, Profiler};
+use pessimistic_proof_core::{
+    generate_pessimistic_proof_with_profiler, NetworkState, PessimisticProofOutput,
+};
+
+/// Reports the phases to the SP1 cycle tracker, which sums the cycles of each
+/// label in the execution report.
+struct CycleTracker;
+
+impl Profiler for CycleTracker {
+    fn start(&mut self, phase: Phase) {
+        println!("cycle-tracker-report-start: {}", phase.name());
+    }
+
+    fn end(&mut self, phase: Phase) {
+        println!("cycle-tracker-report-end: {}", phase.name());
+    }
+}
 
 sp1_zkvm::entrypoint!(main);
 pub fn main() {
-    let initial_state = sp1_zkvm::io::read::<NetworkState>();
-    let batch_header = sp1_zkvm::io::read::<MultiBatchHeader<Keccak256Hasher>>();
+    // The phases are only reported when the host asks for them, so that the
+    // proofs don't pay for the profiling
+    if sp1_zkvm::io::read::<bool>() {
+        run(&mut CycleTracker);
+    } else {
+        run(&mut NoopProfiler);
+    }
+}
+
+fn run(profiler: &mut impl Profiler) {
+    let (initial_state, batch_header) = profiler.measure(Phase::Deserialization, || {
+        (
+            sp1_zkvm::io::read::<NetworkState>(),
+            sp1_zkvm::io::read::<MultiBatchHeader<Keccak256Hasher>>(),
+        )
+    });
 
-    let outputs = generate_pessimistic_proof(initial_state, &batch_header).unwrap();
+    let outputs =
+        generate_pessimistic_proof_with_profiler(initial_state, &batch_header, profiler).unwrap();
 
     let pp_inputs = PessimisticProofOutput::bincode_options()
         .serialize(&outputs)
@@ -0,0 +1,13 @@
+use pp_bench::cli::{main_with, unavailable};
+use pp_prover::{MockProver, PessimisticProver, ProverBackend};
+use pp_sp1_host::runner::Runner;
+
+fn main() -> anyhow::Result<()> {
+    sp1_sdk::utils::setup_logger();
+
+    main_with(&|backend| match backend {
+        ProverBackend::Sp1 => Ok(Box::new(Runner::new()) as Box<dyn PessimisticProver>),
+        ProverBackend::Mock => Ok(Box::new(MockProver::new())),
+        backend => Err(unavailable(backend)),
+    })
+}
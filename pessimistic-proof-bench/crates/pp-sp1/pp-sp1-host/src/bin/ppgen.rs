@@ -1,16 +1,12 @@
 use std::{path::PathBuf, time::Instant};
 
 use clap::Parser;
-use pp_sp1_host::runner::Runner;
-use serde::{Deserialize, Serialize};
-use sp1_sdk::HashableKey;
-use tracing::{info, warn};
-use uuid::Uuid;
-
-use agglayer_types::{Certificate, U256};
-use pessimistic_proof::bridge_exit::{NetworkId, TokenInfo};
-use pessimistic_proof::PessimisticProofOutput;
-use pessimistic_proof_core::{generate_pessimistic_proof, NetworkState};
+use pp_prover::{PessimisticProver, ProofFixture, ProofMode};
+use pp_sp1_host::runner::{ProverKind, Runner, RunnerConfig};
+use tracing::info;
+
+use pessimistic_proof::witness::Witness;
+use pessimistic_proof_core::generate_pessimistic_proof;
 use pessimistic_proof_test_suite::sample_data::{self as data};
 
 /// The arguments for the pp generator.
@@ -25,30 +21,39 @@ struct PPGenArgs {
     #[clap(long, default_value = "10")]
     n_imported_exits: usize,
 
-    /// The optional output directory to write the proofs in JSON. If not set,
-    /// the proof is simply logged.
+    /// The kind of proof to generate: core, compressed, plonk or groth16.
+    /// The fixtures of `--proof-dir` are always plonk proofs.
+    #[clap(long, default_value = "core")]
+    proof_mode: ProofMode,
+
+    /// The hardware generating the proof: cpu or cuda.
+    #[clap(long, default_value = "cpu")]
+    prover: ProverKind,
+
+    /// The directory persisting the proving and verifying keys across runs.
+    /// Defaults to `$PP_KEY_CACHE_DIR`, or `~/.cache/pessimistic-proof/keys`.
+    #[clap(long)]
+    key_cache_dir: Option<PathBuf>,
+
+    /// The optional output directory to write the fixture of a plonk proof in
+    /// JSON, for the verifier contract. If not set, the proof is simply
+    /// logged.
     #[clap(long)]
     proof_dir: Option<PathBuf>,
 
+    /// The optional file to save the proof in, to verify it later with
+    /// `ppverify`. The vkey is written next to it, with the `vkey` extension.
+    #[clap(long, conflicts_with = "proof_dir")]
+    save_proof: Option<PathBuf>,
+
     /// The optional path to the custom sample data.
     #[clap(long)]
     sample_path: Option<PathBuf>,
-}
 
-fn get_events(n: usize, path: Option<PathBuf>) -> Vec<(TokenInfo, U256)> {
-    if let Some(p) = path {
-        data::sample_bridge_exits(p)
-            .cycle()
-            .take(n)
-            .map(|e| (e.token_info, e.amount))
-            .collect::<Vec<_>>()
-    } else {
-        data::sample_bridge_exits_01()
-            .cycle()
-            .take(n)
-            .map(|e| (e.token_info, e.amount))
-            .collect::<Vec<_>>()
-    }
+    /// The optional witness file to prove instead of the sample data, in
+    /// binary or JSON.
+    #[clap(long)]
+    input: Option<PathBuf>,
 }
 
 pub fn main() {
@@ -56,26 +61,25 @@ pub fn main() {
 
     let args = PPGenArgs::parse();
 
-    let mut state = data::sample_state_00();
-    let old_state = state.state_b.clone();
-    let old_network_state = NetworkState::from(old_state.clone());
-
-    let bridge_exits = get_events(args.n_exits, args.sample_path.clone());
-    let imported_bridge_exits = get_events(args.n_imported_exits, args.sample_path);
-
-    let certificate = state.apply_events(&imported_bridge_exits, &bridge_exits);
+    let (witness, certificate) = match &args.input {
+        Some(input) => (
+            Witness::load(input).expect("Failed to load the witness file"),
+            None,
+        ),
+        None => {
+            let inputs = data::sample_inputs(args.n_exits, args.n_imported_exits, args.sample_path);
+            (inputs.witness(), Some(inputs.certificate))
+        }
+    };
+    let old_network_state = witness.initial_state;
+    let multi_batch_header = witness.batch_header;
 
     // info!(
     //     "Certificate {}: [{}]",
-    //     certificate.hash(),
-    //     serde_json::to_string(&certificate).unwrap()
+    //     inputs.certificate.hash(),
+    //     serde_json::to_string(&inputs.certificate).unwrap()
     // );
 
-    let l1_info_root = certificate.l1_info_root().unwrap().unwrap_or_default();
-    let multi_batch_header = old_state
-        .make_multi_batch_header(&certificate, state.get_signer(), l1_info_root)
-        .unwrap();
-
     // Validate inputs by running generate_pessimistic_proof first
     // let mut proof_output: PessimisticProofOutput = None;
     match generate_pessimistic_proof(old_network_state.clone(), &multi_batch_header) {
@@ -90,97 +94,55 @@ pub fn main() {
 
     info!(
         "Generating the proof for {} bridge exit(s) and {} imported bridge exit(s)",
-        bridge_exits.len(),
-        imported_bridge_exits.len()
-    );
-
-    let start = Instant::now();
-    let (proof, vk, new_roots) = Runner::new()
-        .generate_plonk_proof(&old_state.into(), &multi_batch_header)
-        .expect("proving failed");
-    let duration = start.elapsed();
-    info!(
-        "Successfully generated the proof with a latency of {:?}",
-        duration
+        multi_batch_header.bridge_exits.len(),
+        multi_batch_header.imported_bridge_exits.len()
     );
 
-    let vkey = vk.bytes32().to_string();
+    let runner = Runner::from_config(RunnerConfig {
+        proof_mode: args.proof_mode,
+        prover: args.prover,
+        key_cache_dir: args.key_cache_dir,
+    });
+    let vkey = runner.vkey().expect("vkey");
     info!("vkey: {}", vkey);
 
-    println!("proof: {:?}", proof.public_values);
-
-    // let fixture = PessimisticProofFixture {
-    //     certificate,
-    //     pp_inputs: new_roots.into(),
-    //     signer: state.get_signer(),
-    //     vkey: vkey.clone(),
-    //     public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
-    //     proof: format!("0x{}", hex::encode(proof.bytes())),
-    // };
-
-    // if let Some(proof_dir) = args.proof_dir {
-    //     // Save the plonk proof to a json file.
-    //     let proof_path = proof_dir.join(format!(
-    //         "{}-exits-v{}-{}.json",
-    //         args.n_exits,
-    //         &vkey[..8],
-    //         Uuid::new_v4()
-    //     ));
-    //     if let Err(e) = std::fs::create_dir_all(&proof_dir) {
-    //         warn!("Failed to create directory: {e}");
-    //     }
-    //     info!("Writing the proof to {:?}", proof_path);
-    //     std::fs::write(proof_path, serde_json::to_string_pretty(&fixture).unwrap())
-    //         .expect("failed to write fixture");
-    // } else {
-    //     info!("Proof: {:?}", fixture);
-    // }
+    let start = Instant::now();
+    match args.proof_dir {
+        Some(proof_dir) => {
+            let proof = runner
+                .prove_onchain(&old_network_state, &multi_batch_header)
+                .expect("proving failed");
+            info!(
+                "Successfully generated the plonk proof with a latency of {:?}",
+                start.elapsed()
+            );
+
+            let fixture = ProofFixture::new(&proof, vkey, multi_batch_header.signer, certificate)
+                .expect("invalid proof output");
+            println!("proof: {:?}", fixture.pp_inputs);
+
+            let fixture_path = fixture.write(&proof_dir).expect("failed to write fixture");
+            info!("Wrote the fixture to {:?}", fixture_path);
+        }
+        None => {
+            let proof = runner
+                .prove(&old_network_state, &multi_batch_header)
+                .expect("proving failed");
+            info!(
+                "Successfully generated the {} proof with a latency of {:?}",
+                args.proof_mode,
+                start.elapsed()
+            );
+
+            let new_roots = runner.extract_output(&proof).expect("invalid proof output");
+            println!("proof: {:?}", new_roots);
+
+            if let Some(path) = args.save_proof {
+                proof.write(&path).expect("failed to write the proof");
+                std::fs::write(path.with_extension("vkey"), &vkey)
+                    .expect("failed to write the vkey");
+                info!("Saved the proof to {:?}", path);
+            }
+        }
+    }
 }
-
-// #[derive(Debug, Clone, Serialize, Deserialize)]
-// #[serde(rename_all = "kebab-case")]
-// pub struct VerifierInputs {
-//     /// The previous local exit root.
-//     pub prev_local_exit_root: String,
-//     /// The previous pessimistic root.
-//     pub prev_pessimistic_root: String,
-//     /// The l1 info root against which we prove the inclusion of the
-//     /// imported bridge exits.
-//     pub l1_info_root: String,
-//     /// The origin network of the pessimistic proof.
-//     pub origin_network: NetworkId,
-//     /// The consensus hash.
-//     pub consensus_hash: String,
-//     /// The new local exit root.
-//     pub new_local_exit_root: String,
-//     /// The new pessimistic root which commits to the balance and nullifier
-//     /// tree.
-//     pub new_pessimistic_root: String,
-// }
-
-// impl From<PessimisticProofOutput> for VerifierInputs {
-//     fn from(v: PessimisticProofOutput) -> Self {
-//         Self {
-//             prev_local_exit_root: format!("0x{}", hex::encode(v.prev_local_exit_root)),
-//             prev_pessimistic_root: format!("0x{}", hex::encode(v.prev_pessimistic_root)),
-//             l1_info_root: format!("0x{}", hex::encode(v.l1_info_root)),
-//             origin_network: v.origin_network.into(),
-//             consensus_hash: format!("0x{}", hex::encode(v.consensus_hash)),
-//             new_local_exit_root: format!("0x{}", hex::encode(v.new_local_exit_root)),
-//             new_pessimistic_root: format!("0x{}", hex::encode(v.new_pessimistic_root)),
-//         }
-//     }
-// }
-
-// /// A fixture that can be used to test the verification of SP1 zkVM proofs
-// /// inside Solidity.
-// #[derive(Debug, Clone, Serialize, Deserialize)]
-// #[serde(rename_all = "kebab-case")]
-// struct PessimisticProofFixture {
-//     certificate: Certificate,
-//     pp_inputs: VerifierInputs,
-//     signer: Address,
-//     vkey: String,
-//     public_values: String,
-//     proof: String,
-// }
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use pp_prover::ProofFixture;
+use pp_sp1_host::runner::Runner;
+use tracing::info;
+
+/// The arguments for the fixture verifier.
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct VerifyFixtureArgs {
+    /// The fixture file to verify, as written by `ppgen --proof-dir`.
+    fixture: PathBuf,
+}
+
+pub fn main() -> anyhow::Result<()> {
+    sp1_sdk::utils::setup_logger();
+
+    let args = VerifyFixtureArgs::parse();
+    let fixture = ProofFixture::load(&args.fixture)?;
+    fixture.verify(&Runner::new())?;
+    info!(
+        "Verified the fixture {:?}: {:?}",
+        args.fixture, fixture.pp_inputs
+    );
+
+    Ok(())
+}
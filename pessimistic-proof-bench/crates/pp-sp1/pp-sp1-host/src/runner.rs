@@ -1,10 +1,20 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf, str::FromStr, sync::OnceLock};
+
 use bincode::config::Options;
 use pessimistic_proof::NetworkState;
 pub use pessimistic_proof::PessimisticProofOutput;
+use pp_prover::{
+    Execution, KeyCache, OnchainProof, PessimisticProver, Phase, Proof, ProofMode,
+    ProofVerificationError, ProverBackend, ProverError,
+};
+use serde::{Deserialize, Serialize};
 
-use sp1_sdk::{Prover, SP1PublicValues};
 pub use sp1_sdk::{ExecutionReport, SP1Proof};
-use sp1_sdk::{SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey};
+use sp1_sdk::{HashableKey, Prover, SP1PublicValues};
+use sp1_sdk::{
+    SP1ProofMode, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin, SP1VerificationError,
+    SP1VerifyingKey,
+};
 
 use crate::PESSIMISTIC_PROOF_ELF;
 
@@ -14,10 +24,63 @@ pub type MultiBatchHeader = pessimistic_proof::multi_batch_header::MultiBatchHea
 
 pub struct ProofOutput {}
 
+/// Hardware generating the proofs.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProverKind {
+    #[default]
+    Cpu,
+    /// Requires a CUDA device, used through the SP1 GPU prover.
+    Cuda,
+}
+
+impl fmt::Display for ProverKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ProverKind::Cpu => "cpu",
+            ProverKind::Cuda => "cuda",
+        })
+    }
+}
+
+impl FromStr for ProverKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(ProverKind::Cpu),
+            "cuda" => Ok(ProverKind::Cuda),
+            _ => Err(format!("Unknown prover kind: {s}")),
+        }
+    }
+}
+
+/// Configuration of the [`Runner`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RunnerConfig {
+    /// The kind of the proofs generated by [`PessimisticProver::prove`].
+    #[serde(default)]
+    pub proof_mode: ProofMode,
+    #[serde(default)]
+    pub prover: ProverKind,
+    /// The directory persisting the keys across runs, or
+    /// [`KeyCache::default_dir`] if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_cache_dir: Option<PathBuf>,
+}
+
 /// A convenient interface to run the pessimistic proof ELF bytecode.
 pub struct Runner {
-    // client: sp1_sdk::CudaProver,
+    /// Executes the guest program and verifies the proofs, and generates the
+    /// proofs unless proving on the GPU.
     client: sp1_sdk::CpuProver,
+    cuda: Option<sp1_sdk::CudaProver>,
+    proof_mode: ProofMode,
+    key_cache: KeyCache,
+    /// The proving and verifying keys of the guest program, loaded from the
+    /// key cache on first use.
+    keys: OnceLock<(SP1ProvingKey, SP1VerifyingKey)>,
 }
 
 impl Default for Runner {
@@ -27,21 +90,53 @@ impl Default for Runner {
 }
 
 impl Runner {
-    /// Create a new pessimistic proof client.
+    /// Create a new pessimistic proof client, generating core proofs on the
+    /// CPU.
     pub fn new() -> Self {
-        // Self::from_client(sp1_sdk::ProverClient::builder().cuda().build())
-        Self::from_client(sp1_sdk::ProverClient::builder().cpu().build())
+        Self::from_config(RunnerConfig::default())
+    }
+
+    /// Create a new pessimistic proof client from the given configuration.
+    pub fn from_config(config: RunnerConfig) -> Self {
+        let cuda = match config.prover {
+            ProverKind::Cpu => None,
+            ProverKind::Cuda => Some(sp1_sdk::ProverClient::builder().cuda().build()),
+        };
+
+        Self {
+            client: sp1_sdk::ProverClient::builder().cpu().build(),
+            cuda,
+            proof_mode: config.proof_mode,
+            key_cache: config.key_cache_dir.map(KeyCache::new).unwrap_or_default(),
+            keys: OnceLock::new(),
+        }
     }
 
-    /// Create a new pessimistic proof client from a custom generic client.
-    // pub fn from_client(client: sp1_sdk::CudaProver) -> Self {
+    /// Create a new pessimistic proof client from a custom CPU client.
     pub fn from_client(client: sp1_sdk::CpuProver) -> Self {
-        Self { client }
+        Self {
+            client,
+            cuda: None,
+            proof_mode: ProofMode::default(),
+            key_cache: KeyCache::default(),
+            keys: OnceLock::new(),
+        }
+    }
+
+    /// Returns the kind of the proofs generated by this client.
+    pub fn proof_mode(&self) -> ProofMode {
+        self.proof_mode
     }
 
-    /// Convert inputs to stdin.
-    pub fn prepare_stdin(state: &NetworkState, batch_header: &MultiBatchHeader) -> SP1Stdin {
+    /// Convert inputs to stdin. If `profile` is set, the guest reports the
+    /// cycles of each phase to the cycle tracker.
+    pub fn prepare_stdin(
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+        profile: bool,
+    ) -> SP1Stdin {
         let mut stdin = SP1Stdin::new();
+        stdin.write(&profile);
         stdin.write(state);
         stdin.write(batch_header);
         stdin
@@ -60,7 +155,7 @@ impl Runner {
         state: &NetworkState,
         batch_header: &MultiBatchHeader,
     ) -> anyhow::Result<(PessimisticProofOutput, ExecutionReport)> {
-        let stdin = Self::prepare_stdin(state, batch_header);
+        let stdin = Self::prepare_stdin(state, batch_header, true);
         let (public_vals, report) = self.client.execute(PESSIMISTIC_PROOF_ELF, &stdin).run()?;
 
         let output = Self::extract_output(public_vals);
@@ -68,13 +163,41 @@ impl Runner {
         Ok((output, report))
     }
 
-    pub fn get_vkey(&self) -> SP1VerifyingKey {
-        let (_pk, vk) = self.client.setup(PESSIMISTIC_PROOF_ELF);
-        vk
+    /// Returns the cycles of the phases reported by the guest to the cycle
+    /// tracker.
+    pub fn phase_cycles(report: &ExecutionReport) -> BTreeMap<Phase, u64> {
+        report
+            .cycle_tracker
+            .iter()
+            .filter_map(|(label, cycles)| Some((Phase::from_name(label)?, *cycles)))
+            .collect()
+    }
+
+    /// Returns the proving and verifying keys, loaded from the key cache of
+    /// this runner or set up on its first call.
+    pub fn keys(&self) -> Result<&(SP1ProvingKey, SP1VerifyingKey), ProverError> {
+        if let Some(keys) = self.keys.get() {
+            return Ok(keys);
+        }
+
+        // The keys depend on the version of the circuits, besides the ELF
+        let name = format!("keys-{}", self.client.version());
+        let keys = self.key_cache.get_or_insert_with(
+            ProverBackend::Sp1,
+            PESSIMISTIC_PROOF_ELF,
+            &name,
+            || Ok::<_, ProverError>(self.client.setup(PESSIMISTIC_PROOF_ELF)),
+        )?;
+
+        Ok(self.keys.get_or_init(|| keys))
+    }
+
+    pub fn get_vkey(&self) -> Result<&SP1VerifyingKey, ProverError> {
+        Ok(&self.keys()?.1)
     }
 
-    /// Generate one plonk proof.
-    pub fn generate_plonk_proof(
+    /// Generate one proof of the configured mode.
+    pub fn generate_proof(
         &self,
         state: &NetworkState,
         batch_header: &MultiBatchHeader,
@@ -83,12 +206,154 @@ impl Runner {
         SP1VerifyingKey,
         PessimisticProofOutput,
     )> {
-        let stdin = Self::prepare_stdin(state, batch_header);
-        let (pk, vk) = self.client.setup(PESSIMISTIC_PROOF_ELF);
+        let stdin = Self::prepare_stdin(state, batch_header, false);
+        let (pk, vk) = self.keys()?;
 
-        let proof = self.client.prove(&pk, &stdin).run()?;
+        let proof = self.prove_with_mode(pk, &stdin, self.proof_mode)?;
         let output = Self::extract_output(proof.public_values.clone());
 
-        Ok((proof, vk, output))
+        Ok((proof, vk.clone(), output))
+    }
+
+    /// Generate one proof of the given mode, on the configured hardware.
+    fn prove_with_mode(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        mode: ProofMode,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let mode = match mode {
+            ProofMode::Core => SP1ProofMode::Core,
+            ProofMode::Compressed => SP1ProofMode::Compressed,
+            ProofMode::Plonk => SP1ProofMode::Plonk,
+            ProofMode::Groth16 => SP1ProofMode::Groth16,
+        };
+
+        match &self.cuda {
+            Some(cuda) => cuda.prove(pk, stdin).mode(mode).run(),
+            None => self.client.prove(pk, stdin).mode(mode).run(),
+        }
+    }
+}
+
+impl PessimisticProver for Runner {
+    fn backend(&self) -> ProverBackend {
+        ProverBackend::Sp1
+    }
+
+    fn execute(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Execution, ProverError> {
+        let stdin = Self::prepare_stdin(state, batch_header, true);
+        let (public_vals, report) = self
+            .client
+            .execute(PESSIMISTIC_PROOF_ELF, &stdin)
+            .run()
+            .map_err(ProverError::Execution)?;
+
+        Ok(Execution {
+            output: pp_prover::decode_bincode_output(public_vals.as_slice())?,
+            cycles: Some(report.total_instruction_count()),
+            phase_cycles: Self::phase_cycles(&report),
+        })
+    }
+
+    fn prove(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<Proof, ProverError> {
+        let (proof, _vk, _output) = self
+            .generate_proof(state, batch_header)
+            .map_err(ProverError::Proving)?;
+
+        Ok(Proof {
+            backend: self.backend(),
+            bytes: bincode::serialize(&proof)
+                .map_err(|error| ProverError::Proving(error.into()))?,
+            public_values: proof.public_values.to_vec(),
+        })
+    }
+
+    fn verify(&self, proof: &Proof) -> Result<(), ProverError> {
+        proof.ensure_backend(self.backend())?;
+        let sp1_proof: SP1ProofWithPublicValues = bincode::deserialize(&proof.bytes)
+            .map_err(|error| ProverError::ProofDecoding(error.to_string()))?;
+        if sp1_proof.public_values.as_slice() != proof.public_values.as_slice() {
+            return Err(ProofVerificationError::InvalidPublicValues.into());
+        }
+
+        self.client
+            .verify(&sp1_proof, self.get_vkey()?)
+            .map_err(|error| verification_error(error).into())
+    }
+
+    fn set_proof_mode(&mut self, mode: ProofMode) -> Result<(), ProverError> {
+        self.proof_mode = mode;
+        Ok(())
+    }
+
+    fn vkey(&self) -> Result<String, ProverError> {
+        Ok(self.get_vkey()?.bytes32())
+    }
+
+    fn extract_output(&self, proof: &Proof) -> Result<PessimisticProofOutput, ProverError> {
+        proof.ensure_backend(self.backend())?;
+        pp_prover::decode_bincode_output(&proof.public_values)
+    }
+
+    fn prove_onchain(
+        &self,
+        state: &NetworkState,
+        batch_header: &MultiBatchHeader,
+    ) -> Result<OnchainProof, ProverError> {
+        let stdin = Self::prepare_stdin(state, batch_header, false);
+        let (pk, _vk) = self.keys()?;
+        let proof = self
+            .prove_with_mode(pk, &stdin, ProofMode::Plonk)
+            .map_err(ProverError::Proving)?;
+
+        Ok(OnchainProof {
+            backend: self.backend(),
+            bytes: proof.bytes(),
+            public_values: proof.public_values.to_vec(),
+        })
+    }
+
+    fn verify_onchain(&self, proof: &OnchainProof) -> Result<(), ProverError> {
+        if proof.backend != self.backend() {
+            return Err(ProverError::BackendMismatch {
+                expected: self.backend(),
+                got: proof.backend,
+            });
+        }
+
+        sp1_verifier::PlonkVerifier::verify(
+            &proof.bytes,
+            &proof.public_values,
+            &self.get_vkey()?.bytes32(),
+            &sp1_verifier::PLONK_VK_BYTES,
+        )
+        .map_err(|error| ProofVerificationError::Plonk(error.to_string()).into())
+    }
+}
+
+/// Maps the verification errors of the SDK to the ones of the certificates.
+fn verification_error(error: SP1VerificationError) -> ProofVerificationError {
+    match error {
+        SP1VerificationError::InvalidPublicValues => ProofVerificationError::InvalidPublicValues,
+        SP1VerificationError::VersionMismatch(version) => {
+            ProofVerificationError::VersionMismatch(version)
+        }
+        SP1VerificationError::Core(error) => ProofVerificationError::Core(error.to_string()),
+        SP1VerificationError::Recursion(error) => {
+            ProofVerificationError::Recursion(error.to_string())
+        }
+        SP1VerificationError::Plonk(error) => ProofVerificationError::Plonk(error.to_string()),
+        SP1VerificationError::Groth16(error) => ProofVerificationError::Groth16(error.to_string()),
+        #[allow(unreachable_patterns)]
+        error => ProofVerificationError::Core(error.to_string()),
     }
 }
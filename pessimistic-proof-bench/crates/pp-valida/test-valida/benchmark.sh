@@ -53,13 +53,11 @@ run_benchmark() {
 echo "Preparing benchmark inputs..."
 for n in "${SIZES[@]}"; do
     echo "Generating input for $n exits..."
-    # Create JSON input file directly
-    cat > "$INPUT_DIR/benchmark_input_$n.json" << EOF
-{
-    "n_exits": $n,
-    "n_imported_exits": $n
-}
-EOF
+    # Write the witness file of the sample inputs
+    cargo run --release --manifest-path ../../../Cargo.toml \
+        --package pessimistic-proof-test-suite --bin ppwitness -- \
+        --n-exits $n --n-imported-exits $n \
+        --output "$INPUT_DIR/benchmark_input_$n.json"
 done
 
 # Run benchmarks for each size
@@ -1,29 +1,24 @@
 #![no_main]
 
+use std::io::Read;
+
 use valida_rs::io::InputTape;
-use serde::Deserialize;
-use pessimistic_proof::NetworkState;
-use pessimistic_proof::multi_batch_header::MultiBatchHeader;
+use pessimistic_proof::witness::Witness;
 // use pessimistic_proof_core::generate_pessimistic_proof;
 
-
-#[derive(Deserialize)]
-struct BenchmarkInput {
-    initial_state: NetworkState,
-    multi_batch_header: MultiBatchHeader,
-}
-
 #[no_mangle]
 fn main() {
-    // Read prepared benchmark input
-    let mut de = serde_json::Deserializer::from_reader(InputTape);
-    let input: BenchmarkInput = BenchmarkInput::deserialize(&mut de)
-        .expect("could not parse benchmark input");
+    // Read the witness file given as input tape
+    let mut bytes = Vec::new();
+    InputTape
+        .read_to_end(&mut bytes)
+        .expect("could not read the input tape");
+    let input = Witness::from_bytes(&bytes).expect("could not parse the witness file");
 
     // Generate proof
     // let outputs = generate_pessimistic_proof(
     //     input.initial_state,
-    //     &input.multi_batch_header
+    //     &input.batch_header
     // ).expect("proving failed");
 
     // // Print minimal output to verify execution